@@ -1,4 +1,4 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt, sync::atomic::AtomicUsize};
 
 use crate::{
     inner::SlotMap,
@@ -8,6 +8,12 @@ use crate::{
 pub struct SlotHeap<T> {
     ids: Vec<usize>,
     entries: SlotMap<(T, usize)>,
+    #[cfg(feature = "metrics")]
+    sift_ups: u64,
+    #[cfg(feature = "metrics")]
+    sift_downs: u64,
+    #[cfg(feature = "metrics")]
+    comparisons: u64,
 }
 
 impl<T> SlotHeap<T>
@@ -18,6 +24,25 @@ where
         Self {
             ids: Vec::new(),
             entries: SlotMap::new(),
+            #[cfg(feature = "metrics")]
+            sift_ups: 0,
+            #[cfg(feature = "metrics")]
+            sift_downs: 0,
+            #[cfg(feature = "metrics")]
+            comparisons: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            ids: Vec::with_capacity(capacity),
+            entries: SlotMap::with_capacity(capacity),
+            #[cfg(feature = "metrics")]
+            sift_ups: 0,
+            #[cfg(feature = "metrics")]
+            sift_downs: 0,
+            #[cfg(feature = "metrics")]
+            comparisons: 0,
         }
     }
 
@@ -25,6 +50,40 @@ where
         self.entries.len()
     }
 
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity().min(self.ids.capacity())
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn inserts(&self) -> u64 {
+        self.entries.inserts()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn removes(&self) -> u64 {
+        self.entries.removes()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn sift_ups(&self) -> u64 {
+        self.sift_ups
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn sift_downs(&self) -> u64 {
+        self.sift_downs
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn comparisons(&self) -> u64 {
+        self.comparisons
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.ids.reserve(additional);
+        self.entries.reserve(additional)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.entries.len() == 0
     }
@@ -53,6 +112,10 @@ where
         }
     }
 
+    pub unsafe fn peek_unchecked_id(&self) -> usize {
+        unsafe { self.get_unchecked_nth_id(0) }
+    }
+
     pub unsafe fn peek_unchecked(&self) -> &T {
         unsafe {
             let id = self.ids.get_unchecked(0);
@@ -75,10 +138,22 @@ where
         unsafe { &mut self.entries.get_unchecked_mut(id).0 }
     }
 
+    pub unsafe fn get_unchecked_refs(&self, id: usize) -> &AtomicUsize {
+        unsafe { self.entries.get_unchecked_refs(id) }
+    }
+
+    pub unsafe fn get_unchecked_generation(&self, id: usize) -> u32 {
+        unsafe { self.entries.get_unchecked_generation(id) }
+    }
+
     pub unsafe fn get_unchecked_index(&self, id: usize) -> usize {
         unsafe { self.entries.get_unchecked(id).1 }
     }
 
+    pub unsafe fn get_unchecked_nth_id(&self, index: usize) -> usize {
+        unsafe { *self.ids.get_unchecked(index) }
+    }
+
     pub unsafe fn heapify(&mut self, mut index: usize) -> usize {
         unsafe {
             index = self.heapify_up(index);
@@ -90,7 +165,12 @@ where
         unsafe {
             while let Some(up_index) = self.next_up(index) {
                 self.swap_entries(index, up_index);
-                index = up_index
+                index = up_index;
+
+                #[cfg(feature = "metrics")]
+                {
+                    self.sift_ups += 1
+                }
             }
         }
 
@@ -101,39 +181,44 @@ where
         unsafe {
             while let Some(down_index) = self.next_down(index) {
                 self.swap_entries(index, down_index);
-                index = down_index
+                index = down_index;
+
+                #[cfg(feature = "metrics")]
+                {
+                    self.sift_downs += 1
+                }
             }
         }
 
         index
     }
 
-    unsafe fn next_up(&self, index: usize) -> Option<usize> {
+    unsafe fn next_up(&mut self, index: usize) -> Option<usize> {
         index
             .checked_sub(1)
             .map(|x| x / 2)
             .filter(|up_index| unsafe {
-                let id = self.ids.get_unchecked(index);
-                let up_id = self.ids.get_unchecked(*up_index);
-                self.less(*id, *up_id)
+                let id = *self.ids.get_unchecked(index);
+                let up_id = *self.ids.get_unchecked(*up_index);
+                self.less(id, up_id)
             })
     }
 
-    unsafe fn next_down(&self, index: usize) -> Option<usize> {
-        let id = unsafe { self.ids.get_unchecked(index) };
+    unsafe fn next_down(&mut self, index: usize) -> Option<usize> {
+        let id = unsafe { *self.ids.get_unchecked(index) };
         let (left_index, right_index) = (index * 2 + 1, index * 2 + 2);
 
-        if let Some(right_id) = self.ids.get(right_index) {
-            let left_id = unsafe { self.ids.get_unchecked(left_index) };
+        if let Some(&right_id) = self.ids.get(right_index) {
+            let left_id = unsafe { *self.ids.get_unchecked(left_index) };
 
-            if unsafe { self.less(*left_id, *right_id) } {
-                unsafe { self.less(*left_id, *id) }.then_some(left_index)
+            if unsafe { self.less(left_id, right_id) } {
+                unsafe { self.less(left_id, id) }.then_some(left_index)
             } else {
-                unsafe { self.less(*right_id, *id) }.then_some(right_index)
+                unsafe { self.less(right_id, id) }.then_some(right_index)
             }
         } else {
-            let left_id = self.ids.get(left_index)?;
-            unsafe { self.less(*left_id, *id) }.then_some(left_index)
+            let &left_id = self.ids.get(left_index)?;
+            unsafe { self.less(left_id, id) }.then_some(left_index)
         }
     }
 
@@ -147,7 +232,12 @@ where
         }
     }
 
-    unsafe fn less(&self, id0: usize, id1: usize) -> bool {
+    unsafe fn less(&mut self, id0: usize, id1: usize) -> bool {
+        #[cfg(feature = "metrics")]
+        {
+            self.comparisons += 1
+        }
+
         let value0 = unsafe { &self.entries.get_unchecked(id0).0 };
         let value1 = unsafe { &self.entries.get_unchecked(id1).0 };
 
@@ -158,3 +248,18 @@ where
         }
     }
 }
+
+impl<T> fmt::Debug for SlotHeap<T>
+where
+    T: PartialOrd + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(
+                self.ids
+                    .iter()
+                    .map(|&id| unsafe { &self.entries.get_unchecked(id).0 }),
+            )
+            .finish()
+    }
+}
@@ -1,7 +1,8 @@
 use std::{
     alloc::{self, Layout},
     mem::{self, MaybeUninit},
-    ptr::NonNull,
+    ptr::{self, NonNull},
+    sync::atomic::AtomicUsize,
 };
 
 pub struct SlotMap<T> {
@@ -9,12 +10,18 @@ pub struct SlotMap<T> {
     capacity: usize,
     len: usize,
     next: usize,
+    #[cfg(feature = "metrics")]
+    inserts: u64,
+    #[cfg(feature = "metrics")]
+    removes: u64,
 }
 
 pub struct Entry<T> {
     pub value: MaybeUninit<T>,
     pub index: usize,
     pub id: usize,
+    pub generation: u32,
+    pub refs: AtomicUsize,
 }
 
 impl<T> SlotMap<T> {
@@ -24,13 +31,56 @@ impl<T> SlotMap<T> {
             capacity: 0,
             len: 0,
             next: 0,
+            #[cfg(feature = "metrics")]
+            inserts: 0,
+            #[cfg(feature = "metrics")]
+            removes: 0,
         }
     }
 
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::try_with_capacity(capacity).expect("capacity overflow")
+    }
+
+    pub fn try_with_capacity(capacity: usize) -> Option<Self> {
+        let mut map = Self::new();
+
+        if capacity != 0 {
+            let layout = Layout::array::<Entry<T>>(capacity).ok()?;
+            unsafe { map.try_grow_to(capacity, layout) }.ok()?
+        }
+
+        Some(map)
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
 
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn inserts(&self) -> u64 {
+        self.inserts
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn removes(&self) -> u64 {
+        self.removes
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("capacity overflow");
+
+        if required > self.capacity {
+            let capacity = required.max(self.capacity * 2);
+            let layout = Layout::array::<Entry<T>>(capacity).expect("capacity overflow");
+            unsafe { self.grow_to(capacity, layout) }
+        }
+    }
+
     pub fn insert(&mut self, value: T) -> usize {
         if self.next == self.capacity {
             unsafe { self.grow() }
@@ -40,9 +90,16 @@ impl<T> SlotMap<T> {
         let value_entry = unsafe { self.entries.add(id).as_mut() };
         self.next = mem::replace(&mut value_entry.index, self.len);
         value_entry.value.write(value);
+        value_entry.refs = AtomicUsize::new(0);
         let id_entry = unsafe { self.entries.add(self.len).as_mut() };
         id_entry.id = id;
         self.len += 1;
+
+        #[cfg(feature = "metrics")]
+        {
+            self.inserts += 1
+        }
+
         id
     }
 
@@ -50,9 +107,15 @@ impl<T> SlotMap<T> {
         let value_entry = unsafe { self.entries.add(id).as_mut() };
         let value = unsafe { value_entry.value.assume_init_read() };
         let index = mem::replace(&mut value_entry.index, self.next);
+        value_entry.generation = value_entry.generation.wrapping_add(1);
         self.next = id;
         self.len -= 1;
 
+        #[cfg(feature = "metrics")]
+        {
+            self.removes += 1
+        }
+
         if index != self.len {
             let id_entry = unsafe { self.entries.add(index).as_mut() };
             let last_id_entry = unsafe { self.entries.add(self.len).as_mut() };
@@ -65,6 +128,27 @@ impl<T> SlotMap<T> {
         value
     }
 
+    pub fn contains(&self, id: usize, generation: u32) -> bool {
+        self.is_occupied(id) && unsafe { self.get_unchecked_generation(id) } == generation
+    }
+
+    pub fn is_occupied(&self, id: usize) -> bool {
+        if id >= self.capacity {
+            return false;
+        }
+
+        let index = unsafe { self.entries.add(id).as_ref().index };
+        index < self.len && unsafe { self.get_unchecked_nth_id(index) } == id
+    }
+
+    pub unsafe fn get_unchecked_generation(&self, id: usize) -> u32 {
+        unsafe { self.entries.add(id).as_ref().generation }
+    }
+
+    pub unsafe fn get_unchecked_refs(&self, id: usize) -> &AtomicUsize {
+        unsafe { &self.entries.add(id).as_ref().refs }
+    }
+
     pub unsafe fn get_unchecked(&self, id: usize) -> &T {
         unsafe { self.entries.add(id).as_ref().value.assume_init_ref() }
     }
@@ -73,41 +157,56 @@ impl<T> SlotMap<T> {
         unsafe { self.entries.add(id).as_mut().value.assume_init_mut() }
     }
 
-    pub unsafe fn get_unchecked_nth(&self, index: usize) -> &T {
-        let id = unsafe { self.get_unchecked_nth_id(index) };
-        unsafe { self.get_unchecked(id) }
-    }
-
     pub unsafe fn get_unchecked_nth_id(&self, index: usize) -> usize {
         unsafe { self.entries.add(index).as_ref().id }
     }
 
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, u32, &mut T)> + '_ {
+        let entries = self.entries.as_ptr();
+        (0..self.len).map(move |index| unsafe {
+            let id = ptr::addr_of!((*entries.add(index)).id).read();
+            let entry = entries.add(id);
+            let generation = ptr::addr_of!((*entry).generation).read();
+            let value = &mut *ptr::addr_of_mut!((*entry).value);
+            (id, generation, value.assume_init_mut())
+        })
+    }
+
     unsafe fn grow(&mut self) {
-        let old_capacity = self.capacity;
-        self.capacity = if self.capacity == 0 {
+        let capacity = if self.capacity == 0 {
             1
         } else {
             self.capacity * 2
         };
-        self.entries = unsafe {
-            let new_layout = Layout::array::<Entry<T>>(self.capacity).unwrap_unchecked();
-            let ptr = if old_capacity == 0 {
-                alloc::alloc(new_layout)
+        let layout = Layout::array::<Entry<T>>(capacity).expect("capacity overflow");
+        unsafe { self.grow_to(capacity, layout) }
+    }
+
+    unsafe fn grow_to(&mut self, capacity: usize, layout: Layout) {
+        if let Err(layout) = unsafe { self.try_grow_to(capacity, layout) } {
+            alloc::handle_alloc_error(layout)
+        }
+    }
+
+    unsafe fn try_grow_to(&mut self, capacity: usize, layout: Layout) -> Result<(), Layout> {
+        let ptr = unsafe {
+            if self.capacity == 0 {
+                alloc::alloc(layout)
             } else {
-                let old_layout = Layout::array::<Entry<T>>(old_capacity).unwrap_unchecked();
-                alloc::realloc(
-                    self.entries.as_ptr() as *mut u8,
-                    old_layout,
-                    new_layout.size(),
-                )
-            };
-            NonNull::new_unchecked(ptr as *mut Entry<T>)
+                let old_layout = Layout::array::<Entry<T>>(self.capacity).unwrap_unchecked();
+                alloc::realloc(self.entries.as_ptr() as *mut u8, old_layout, layout.size())
+            }
         };
+        self.entries = NonNull::new(ptr as *mut Entry<T>).ok_or(layout)?;
+        let old_capacity = mem::replace(&mut self.capacity, capacity);
 
         for i in old_capacity..self.capacity {
             let entry = unsafe { self.entries.add(i).as_mut() };
-            entry.index = i + 1
+            entry.index = i + 1;
+            entry.generation = 0
         }
+
+        Ok(())
     }
 }
 
@@ -4,7 +4,20 @@ mod inner;
 mod util;
 
 pub mod slotheap;
+pub mod slotlockmap;
 pub mod slotmap;
 
 pub use slotheap::*;
+pub use slotlockmap::*;
 pub use slotmap::*;
+
+/// With the `deadlock_detection` feature, a thread that would wait forever for a shard or heap
+/// lock it already holds panics instead, naming the lock, the guard types involved and the
+/// handle that asked for it. Cycles between threads are reported by this function.
+///
+/// Locks are attributed to the thread that acquired them until they are released, even if the
+/// guard is sent to another thread in between; a thread that waits for a lock whose guard it sent
+/// away may then be reported as deadlocked. The refs yielded by the rayon parallel iterators are
+/// not attributed to any thread.
+#[cfg(feature = "deadlock_detection")]
+pub use parking_lot::deadlock::check_deadlock;
@@ -1,13 +1,37 @@
 //! Thread-safe slot min-heap with stable RAII handle.
 
-use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use std::{
-    fmt,
+    cmp, fmt,
+    hash::{Hash, Hasher},
     mem::{self, ManuallyDrop},
+    ptr,
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
 };
 use triomphe::Arc;
 
-use crate::inner;
+use crate::{
+    inner,
+    util::{self, RwLock, RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard},
+};
+
+mod key;
+mod mapped;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "serde")]
+mod serde;
+
+#[cfg(feature = "serde")]
+pub use self::serde::*;
+pub use key::*;
+pub use mapped::*;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+#[cfg(feature = "rayon")]
+pub use par::*;
 
 /// Thread-safe slot min-heap with stable RAII handle.
 ///
@@ -23,10 +47,39 @@ where
     /// Creates a new empty min-heap.
     pub fn new() -> Self {
         Self {
-            inner: Arc::new(inner::SlotHeap::new().into()),
+            inner: Arc::new(util::heap_lock(inner::SlotHeap::new())),
+        }
+    }
+
+    /// Creates a new empty min-heap with room for at least `capacity` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity overflows, and aborts like [`Vec::reserve`] if the allocation fails.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(util::heap_lock(inner::SlotHeap::with_capacity(capacity))),
         }
     }
 
+    /// Returns the number of elements the heap can hold without reallocating.
+    ///
+    /// Time complexity: O(1)
+    pub fn capacity(&self) -> usize {
+        self.inner.read().capacity()
+    }
+
+    /// Reserves room for at least `additional` more elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity overflows, and aborts like [`Vec::reserve`] if the allocation fails.
+    ///
+    /// Time complexity: O(capacity)
+    pub fn reserve(&self, additional: usize) {
+        self.inner.write().reserve(additional)
+    }
+
     /// Returns the number of elements in the heap.
     ///
     /// Time complexity: O(1)
@@ -41,6 +94,13 @@ where
         self.len() == 0
     }
 
+    /// Returns whether `id` is a handle of this heap.
+    ///
+    /// Time complexity: O(1)
+    pub fn contains(&self, id: &SlotHeapId<T>) -> bool {
+        Arc::ptr_eq(&self.inner, &id.from)
+    }
+
     /// Inserts a value and returns its handle and whether it became the new minimum.
     ///
     /// Time complexity: O(log n)
@@ -48,7 +108,37 @@ where
         let from = ManuallyDrop::new(self.inner.clone());
         let mut guard = self.inner.write();
         let (id, is_top) = guard.insert(value);
-        (SlotHeapId { from, id }, is_top)
+        let generation = unsafe { guard.get_unchecked_generation(id) };
+        (
+            SlotHeapId {
+                from,
+                id,
+                generation,
+            },
+            is_top,
+        )
+    }
+
+    /// Inserts a value and returns a reference-counted handle to it and whether it became the new
+    /// minimum.
+    ///
+    /// The value is removed when the last clone of the handle is dropped.
+    ///
+    /// Time complexity: O(log n)
+    pub fn insert_shared(&self, value: T) -> (SlotHeapSharedId<T>, bool) {
+        let from = ManuallyDrop::new(self.inner.clone());
+        let mut guard = self.inner.write();
+        let (id, is_top) = guard.insert(value);
+        let generation = unsafe { guard.get_unchecked_generation(id) };
+        unsafe { guard.get_unchecked_refs(id) }.store(1, Ordering::Relaxed);
+        (
+            SlotHeapSharedId {
+                from,
+                id,
+                generation,
+            },
+            is_top,
+        )
     }
 
     /// Returns a shared reference to the minimum element, or `None` if the heap is empty.
@@ -71,6 +161,60 @@ where
             dirty: false,
         })
     }
+
+    /// Like [`peek`](Self::peek), but returns `None` instead of blocking if the lock is held.
+    ///
+    /// Returns `Some(None)` if the lock was acquired but the heap is empty.
+    ///
+    /// Time complexity: O(1)
+    pub fn try_peek(&self) -> Option<Option<SlotHeapPeek<'_, T>>> {
+        let guard = self.inner.try_read()?;
+        Some((!guard.is_empty()).then(|| SlotHeapPeek { guard }))
+    }
+
+    /// Like [`try_peek`](Self::try_peek), but waits up to `timeout` for the lock.
+    pub fn peek_for(&self, timeout: Duration) -> Option<Option<SlotHeapPeek<'_, T>>> {
+        let guard = self.inner.try_read_for(timeout)?;
+        Some((!guard.is_empty()).then(|| SlotHeapPeek { guard }))
+    }
+
+    /// Like [`try_peek`](Self::try_peek), but waits until `deadline` for the lock.
+    pub fn peek_until(&self, deadline: Instant) -> Option<Option<SlotHeapPeek<'_, T>>> {
+        let guard = self.inner.try_read_until(deadline)?;
+        Some((!guard.is_empty()).then(|| SlotHeapPeek { guard }))
+    }
+
+    /// Like [`peek_mut`](Self::peek_mut), but returns `None` instead of blocking if the lock is
+    /// held.
+    ///
+    /// Returns `Some(None)` if the lock was acquired but the heap is empty.
+    ///
+    /// Time complexity: O(1)
+    pub fn try_peek_mut(&self) -> Option<Option<SlotHeapPeekMut<'_, T>>> {
+        let guard = self.inner.try_write()?;
+        Some((!guard.is_empty()).then(|| SlotHeapPeekMut {
+            guard,
+            dirty: false,
+        }))
+    }
+
+    /// Like [`try_peek_mut`](Self::try_peek_mut), but waits up to `timeout` for the lock.
+    pub fn peek_mut_for(&self, timeout: Duration) -> Option<Option<SlotHeapPeekMut<'_, T>>> {
+        let guard = self.inner.try_write_for(timeout)?;
+        Some((!guard.is_empty()).then(|| SlotHeapPeekMut {
+            guard,
+            dirty: false,
+        }))
+    }
+
+    /// Like [`try_peek_mut`](Self::try_peek_mut), but waits until `deadline` for the lock.
+    pub fn peek_mut_until(&self, deadline: Instant) -> Option<Option<SlotHeapPeekMut<'_, T>>> {
+        let guard = self.inner.try_write_until(deadline)?;
+        Some((!guard.is_empty()).then(|| SlotHeapPeekMut {
+            guard,
+            dirty: false,
+        }))
+    }
 }
 
 impl<T> Default for SlotHeap<T>
@@ -85,12 +229,16 @@ where
 /// Stable RAII handle to an value in a [`SlotHeap`].
 ///
 /// Dropping it removes the value from the heap.
+///
+/// Handles compare, hash and order by heap, slot and generation. Heaps are ordered by address,
+/// which is stable while any of their handles is alive.
 pub struct SlotHeapId<T>
 where
     T: PartialOrd,
 {
     from: ManuallyDrop<Arc<RwLock<inner::SlotHeap<T>>>>,
     id: usize,
+    generation: u32,
 }
 
 impl<T> SlotHeapId<T>
@@ -101,7 +249,7 @@ where
     ///
     /// Time complexity: O(log n)
     pub fn into_inner(mut self) -> (T, bool) {
-        let mut guard = self.from.write();
+        let mut guard = self.lock_write();
         let item = unsafe { guard.remove_unchecked(self.id) };
         drop(guard);
         unsafe { ManuallyDrop::drop(&mut self.from) };
@@ -109,12 +257,46 @@ where
         item
     }
 
+    /// Moves the element into `heap` and returns its new handle.
+    ///
+    /// Both heaps are write-locked together, in address order, so the element is never
+    /// unreachable and two opposite moves cannot deadlock. If `heap` already holds the element,
+    /// the handle is returned as is.
+    ///
+    /// Time complexity: O(log n + log m)
+    pub fn move_to(mut self, heap: &SlotHeap<T>) -> SlotHeapId<T> {
+        if Arc::ptr_eq(&self.from, &heap.inner) {
+            return self;
+        }
+
+        let (mut source, mut target) = if self.from.as_ptr() < heap.inner.as_ptr() {
+            let source = self.lock_write();
+            (source, heap.inner.write())
+        } else {
+            let target = heap.inner.write();
+            (self.lock_write(), target)
+        };
+
+        let (value, _) = unsafe { source.remove_unchecked(self.id) };
+        let (id, _) = target.insert(value);
+        let generation = unsafe { target.get_unchecked_generation(id) };
+        drop((source, target));
+        unsafe { ManuallyDrop::drop(&mut self.from) };
+        mem::forget(self);
+
+        SlotHeapId {
+            from: ManuallyDrop::new(heap.inner.clone()),
+            id,
+            generation,
+        }
+    }
+
     /// Returns an immutable reference to the element, holding a read lock until the ref is dropped.
     ///
     /// Time complexity: O(1)
     pub fn get(&self) -> SlotHeapRef<'_, T> {
         SlotHeapRef {
-            guard: self.from.read(),
+            guard: self.lock_read(),
             id: self.id,
         }
     }
@@ -126,11 +308,116 @@ where
     /// Time complexity: O(1)
     pub fn get_mut(&self) -> SlotHeapRefMut<'_, T> {
         SlotHeapRefMut {
-            guard: self.from.write(),
+            guard: self.lock_write(),
             id: self.id,
             dirty: false,
         }
     }
+
+    /// Like [`get`](Self::get), but returns `None` instead of blocking if the lock is held.
+    ///
+    /// Time complexity: O(1)
+    pub fn try_get(&self) -> Option<SlotHeapRef<'_, T>> {
+        Some(SlotHeapRef {
+            guard: self.from.try_read()?,
+            id: self.id,
+        })
+    }
+
+    /// Like [`get`](Self::get), but gives up and returns `None` after `timeout`.
+    pub fn get_for(&self, timeout: Duration) -> Option<SlotHeapRef<'_, T>> {
+        Some(SlotHeapRef {
+            guard: self.from.try_read_for(timeout)?,
+            id: self.id,
+        })
+    }
+
+    /// Like [`get`](Self::get), but gives up and returns `None` at `deadline`.
+    pub fn get_until(&self, deadline: Instant) -> Option<SlotHeapRef<'_, T>> {
+        Some(SlotHeapRef {
+            guard: self.from.try_read_until(deadline)?,
+            id: self.id,
+        })
+    }
+
+    /// Like [`get_mut`](Self::get_mut), but returns `None` instead of blocking if the lock is held.
+    ///
+    /// Time complexity: O(1)
+    pub fn try_get_mut(&self) -> Option<SlotHeapRefMut<'_, T>> {
+        Some(SlotHeapRefMut {
+            guard: self.from.try_write()?,
+            id: self.id,
+            dirty: false,
+        })
+    }
+
+    /// Like [`get_mut`](Self::get_mut), but gives up and returns `None` after `timeout`.
+    pub fn get_mut_for(&self, timeout: Duration) -> Option<SlotHeapRefMut<'_, T>> {
+        Some(SlotHeapRefMut {
+            guard: self.from.try_write_for(timeout)?,
+            id: self.id,
+            dirty: false,
+        })
+    }
+
+    /// Like [`get_mut`](Self::get_mut), but gives up and returns `None` at `deadline`.
+    pub fn get_mut_until(&self, deadline: Instant) -> Option<SlotHeapRefMut<'_, T>> {
+        Some(SlotHeapRefMut {
+            guard: self.from.try_write_until(deadline)?,
+            id: self.id,
+            dirty: false,
+        })
+    }
+
+    /// Returns an immutable reference to the element, holding an upgradable read lock until the
+    /// ref is dropped.
+    ///
+    /// Only one upgradable lock can be held at a time, but it coexists with plain readers. Use
+    /// [`SlotHeapRefUpgradable::upgrade`] to mutate without letting another writer in between.
+    ///
+    /// Time complexity: O(1)
+    pub fn get_upgradable(&self) -> SlotHeapRefUpgradable<'_, T> {
+        SlotHeapRefUpgradable {
+            guard: self.lock_upgradable(),
+            id: self.id,
+        }
+    }
+
+    /// Converts the handle into a reference-counted handle without moving the value.
+    ///
+    /// Time complexity: O(1)
+    pub fn into_shared(mut self) -> SlotHeapSharedId<T> {
+        let guard = self.lock_read();
+        unsafe { guard.get_unchecked_refs(self.id) }.store(1, Ordering::Relaxed);
+        drop(guard);
+        let from = ManuallyDrop::new(unsafe { ManuallyDrop::take(&mut self.from) });
+        let (id, generation) = (self.id, self.generation);
+        mem::forget(self);
+        SlotHeapSharedId {
+            from,
+            id,
+            generation,
+        }
+    }
+}
+
+impl<T> SlotHeapId<T>
+where
+    T: PartialOrd,
+{
+    fn identity(&self) -> (*const RwLock<inner::SlotHeap<T>>, usize, u32) {
+        (self.from.as_ptr(), self.id, self.generation)
+    }
+
+    fn lock_read(&self) -> RwLockReadGuard<'_, inner::SlotHeap<T>> {
+        util::requesting(self, || self.from.read())
+    }
+    fn lock_write(&self) -> RwLockWriteGuard<'_, inner::SlotHeap<T>> {
+        util::requesting(self, || self.from.write())
+    }
+    fn lock_upgradable(&self) -> RwLockUpgradableReadGuard<'_, inner::SlotHeap<T>> {
+        util::requesting(self, || self.from.upgradable_read())
+    }
 }
 
 impl<T> fmt::Debug for SlotHeapId<T>
@@ -139,24 +426,230 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SlotHeapId")
-            .field("from", &self.from.as_ptr())
             .field("id", &self.id)
+            .field("generation", &self.generation)
             .finish()
     }
 }
 
+impl<T> PartialEq for SlotHeapId<T>
+where
+    T: PartialOrd,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl<T> Eq for SlotHeapId<T> where T: PartialOrd {}
+
+impl<T> PartialOrd for SlotHeapId<T>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for SlotHeapId<T>
+where
+    T: PartialOrd,
+{
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.identity().cmp(&other.identity())
+    }
+}
+
+impl<T> Hash for SlotHeapId<T>
+where
+    T: PartialOrd,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state)
+    }
+}
+
 impl<T> Drop for SlotHeapId<T>
 where
     T: PartialOrd,
 {
     fn drop(&mut self) {
-        let mut guard = self.from.write();
+        let mut guard = self.lock_write();
         unsafe { guard.remove_unchecked(self.id) };
         drop(guard);
         unsafe { ManuallyDrop::drop(&mut self.from) }
     }
 }
 
+/// Reference-counted handle to a value in a [`SlotHeap`].
+///
+/// Cloning it shares ownership of the value; the count is stored in the heap entry itself.
+/// Dropping the last clone removes the value from the heap.
+///
+/// Handles compare, hash and order like [`SlotHeapId`], so clones are equal.
+pub struct SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    from: ManuallyDrop<Arc<RwLock<inner::SlotHeap<T>>>>,
+    id: usize,
+    generation: u32,
+}
+
+impl<T> SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    /// Returns the number of handles sharing the value.
+    ///
+    /// Time complexity: O(1)
+    pub fn strong_count(&self) -> usize {
+        let guard = self.lock_read();
+        unsafe { guard.get_unchecked_refs(self.id) }.load(Ordering::Relaxed)
+    }
+
+    /// Takes the value out of the heap if this is the only handle and returns it and whether it
+    /// was the minimum, otherwise returns the handle.
+    ///
+    /// Time complexity: O(log n)
+    pub fn try_unwrap(mut self) -> Result<(T, bool), Self> {
+        let mut guard = self.lock_write();
+
+        if unsafe { guard.get_unchecked_refs(self.id) }.load(Ordering::Relaxed) != 1 {
+            drop(guard);
+            return Err(self);
+        }
+
+        let item = unsafe { guard.remove_unchecked(self.id) };
+        drop(guard);
+        unsafe { ManuallyDrop::drop(&mut self.from) };
+        mem::forget(self);
+        Ok(item)
+    }
+
+    /// Returns an immutable reference to the element, holding a read lock until the ref is dropped.
+    ///
+    /// Time complexity: O(1)
+    pub fn get(&self) -> SlotHeapRef<'_, T> {
+        SlotHeapRef {
+            guard: self.lock_read(),
+            id: self.id,
+        }
+    }
+
+    /// Returns a mutable reference to the element, holding a write lock until the ref is dropped.
+    ///
+    /// If the value is mutated, the heap is re-heapified on drop of the returned guard.
+    ///
+    /// Time complexity: O(1)
+    pub fn get_mut(&self) -> SlotHeapRefMut<'_, T> {
+        SlotHeapRefMut {
+            guard: self.lock_write(),
+            id: self.id,
+            dirty: false,
+        }
+    }
+}
+
+impl<T> Clone for SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    fn clone(&self) -> Self {
+        let guard = self.lock_read();
+        unsafe { guard.get_unchecked_refs(self.id) }.fetch_add(1, Ordering::Relaxed);
+        drop(guard);
+        Self {
+            from: self.from.clone(),
+            id: self.id,
+            generation: self.generation,
+        }
+    }
+}
+
+impl<T> SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    fn identity(&self) -> (*const RwLock<inner::SlotHeap<T>>, usize, u32) {
+        (self.from.as_ptr(), self.id, self.generation)
+    }
+
+    fn lock_read(&self) -> RwLockReadGuard<'_, inner::SlotHeap<T>> {
+        util::requesting(self, || self.from.read())
+    }
+    fn lock_write(&self) -> RwLockWriteGuard<'_, inner::SlotHeap<T>> {
+        util::requesting(self, || self.from.write())
+    }
+}
+
+impl<T> fmt::Debug for SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotHeapSharedId")
+            .field("id", &self.id)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+impl<T> PartialEq for SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl<T> Eq for SlotHeapSharedId<T> where T: PartialOrd {}
+
+impl<T> PartialOrd for SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.identity().cmp(&other.identity())
+    }
+}
+
+impl<T> Hash for SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state)
+    }
+}
+
+impl<T> Drop for SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    fn drop(&mut self) {
+        let mut guard = self.lock_write();
+
+        if unsafe { guard.get_unchecked_refs(self.id) }.fetch_sub(1, Ordering::Relaxed) == 1 {
+            unsafe { guard.remove_unchecked(self.id) };
+        }
+
+        drop(guard);
+        unsafe { ManuallyDrop::drop(&mut self.from) }
+    }
+}
+
 /// Immutable reference to the minimum element of a [`SlotHeap`], holding a read lock.
 pub struct SlotHeapPeek<'a, T>
 where
@@ -204,14 +697,11 @@ where
     ///
     /// Time complexity: O(log n) if the element was mutated, O(1) otherwise.
     pub fn finish(mut self) -> bool {
-        let is_top = if self.dirty {
+        if mem::take(&mut self.dirty) {
             unsafe { self.guard.heapify_down(0) == 0 }
         } else {
             true
-        };
-
-        mem::forget(self);
-        is_top
+        }
     }
 
     fn deref(&self) -> &T {
@@ -261,6 +751,48 @@ where
     }
 }
 
+/// Immutable reference to an element in a [`SlotHeap`], holding an upgradable read lock.
+pub struct SlotHeapRefUpgradable<'a, T>
+where
+    T: PartialOrd,
+{
+    guard: RwLockUpgradableReadGuard<'a, inner::SlotHeap<T>>,
+    id: usize,
+}
+
+#[reflica::reflica]
+impl<T> SlotHeapRefUpgradable<'_, T>
+where
+    T: PartialOrd,
+{
+    /// Returns whether this element is the current minimum (top) of the heap.
+    ///
+    /// Time complexity: O(1)
+    pub fn is_top(&self) -> bool {
+        unsafe { self.guard.get_unchecked_index(self.id) == 0 }
+    }
+
+    fn deref(&self) -> &T {
+        unsafe { self.guard.get_unchecked(self.id) }
+    }
+}
+
+impl<'a, T> SlotHeapRefUpgradable<'a, T>
+where
+    T: PartialOrd,
+{
+    /// Atomically upgrades to a write lock, waiting for other readers to leave.
+    ///
+    /// No other writer can modify the element between this ref and the returned one.
+    pub fn upgrade(self) -> SlotHeapRefMut<'a, T> {
+        SlotHeapRefMut {
+            guard: RwLockUpgradableReadGuard::upgrade(self.guard),
+            id: self.id,
+            dirty: false,
+        }
+    }
+}
+
 /// Mutable reference to an element in a [`SlotHeap`], holding a write lock.
 ///
 /// If the value is mutated, the heap is re-heapified on drop of the returned guard.
@@ -298,15 +830,12 @@ where
     ///
     /// Time complexity: O(log n) if the element was mutated, O(1) otherwise.
     pub fn finish(mut self) -> bool {
-        let is_top = if self.dirty {
+        if mem::take(&mut self.dirty) {
             let index = unsafe { self.guard.get_unchecked_index(self.id) };
             unsafe { self.guard.heapify(index) == 0 }
         } else {
             true
-        };
-
-        mem::forget(self);
-        is_top
+        }
     }
 
     fn deref(&self) -> &T {
@@ -319,6 +848,29 @@ where
     }
 }
 
+impl<'a, T> SlotHeapRefMut<'a, T>
+where
+    T: PartialOrd,
+{
+    /// Atomically downgrades to a read lock, re-heapifying first if the value was mutated.
+    ///
+    /// No other writer can modify the heap between this ref and the returned one.
+    ///
+    /// Time complexity: O(log n) if the element was mutated, O(1) otherwise.
+    pub fn downgrade(mut self) -> SlotHeapRef<'a, T> {
+        if mem::take(&mut self.dirty) {
+            let index = unsafe { self.guard.get_unchecked_index(self.id) };
+            unsafe { self.guard.heapify(index) };
+        }
+
+        let this = ManuallyDrop::new(self);
+        SlotHeapRef {
+            guard: RwLockWriteGuard::downgrade(unsafe { ptr::read(&this.guard) }),
+            id: this.id,
+        }
+    }
+}
+
 impl<T> Drop for SlotHeapRefMut<'_, T>
 where
     T: PartialOrd,
@@ -333,12 +885,76 @@ where
     }
 }
 
+impl<T> fmt::Debug for SlotHeap<T>
+where
+    T: PartialOrd + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("SlotHeap");
+
+        match self.inner.try_read() {
+            Some(guard) => d.field("values", &*guard),
+            None => d.field("values", &format_args!("<locked>")),
+        };
+
+        d.finish()
+    }
+}
+
+impl<T> fmt::Debug for SlotHeapPeek<'_, T>
+where
+    T: PartialOrd + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> fmt::Debug for SlotHeapPeekMut<'_, T>
+where
+    T: PartialOrd + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> fmt::Debug for SlotHeapRef<'_, T>
+where
+    T: PartialOrd + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> fmt::Debug for SlotHeapRefUpgradable<'_, T>
+where
+    T: PartialOrd + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> fmt::Debug for SlotHeapRefMut<'_, T>
+where
+    T: PartialOrd + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
 unsafe impl<T> Send for SlotHeap<T> where T: Send + PartialOrd {}
 unsafe impl<T> Sync for SlotHeap<T> where T: Send + Sync + PartialOrd {}
 
 unsafe impl<T> Send for SlotHeapId<T> where T: Send + PartialOrd {}
 unsafe impl<T> Sync for SlotHeapId<T> where T: Send + Sync + PartialOrd {}
 
+unsafe impl<T> Send for SlotHeapSharedId<T> where T: Send + Sync + PartialOrd {}
+unsafe impl<T> Sync for SlotHeapSharedId<T> where T: Send + Sync + PartialOrd {}
+
 unsafe impl<T> Send for SlotHeapPeek<'_, T> where T: Send + Sync + PartialOrd {}
 unsafe impl<T> Sync for SlotHeapPeek<'_, T> where T: Send + Sync + PartialOrd {}
 
@@ -348,5 +964,8 @@ unsafe impl<T> Sync for SlotHeapPeekMut<'_, T> where T: Send + Sync + PartialOrd
 unsafe impl<T> Send for SlotHeapRef<'_, T> where T: Send + Sync + PartialOrd {}
 unsafe impl<T> Sync for SlotHeapRef<'_, T> where T: Send + Sync + PartialOrd {}
 
+unsafe impl<T> Send for SlotHeapRefUpgradable<'_, T> where T: Send + Sync + PartialOrd {}
+unsafe impl<T> Sync for SlotHeapRefUpgradable<'_, T> where T: Send + Sync + PartialOrd {}
+
 unsafe impl<T> Send for SlotHeapRefMut<'_, T> where T: Send + PartialOrd {}
 unsafe impl<T> Sync for SlotHeapRefMut<'_, T> where T: Send + Sync + PartialOrd {}
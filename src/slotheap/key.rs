@@ -0,0 +1,42 @@
+use crate::{SlotHeapId, SlotHeapSharedId};
+
+/// Lightweight, non-owning key to an element in a [`SlotHeap`](crate::SlotHeap).
+///
+/// It identifies an element across serialization: a restored heap maps each serialized key to
+/// the handle of the recreated element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlotHeapKey {
+    id: usize,
+    generation: u32,
+}
+
+impl SlotHeapKey {
+    pub(crate) fn new(id: usize, generation: u32) -> Self {
+        Self { id, generation }
+    }
+}
+
+impl<T> SlotHeapId<T>
+where
+    T: PartialOrd,
+{
+    /// Returns the key of the element.
+    ///
+    /// Time complexity: O(1)
+    pub fn key(&self) -> SlotHeapKey {
+        SlotHeapKey::new(self.id, self.generation)
+    }
+}
+
+impl<T> SlotHeapSharedId<T>
+where
+    T: PartialOrd,
+{
+    /// Returns the key of the element.
+    ///
+    /// Time complexity: O(1)
+    pub fn key(&self) -> SlotHeapKey {
+        SlotHeapKey::new(self.id, self.generation)
+    }
+}
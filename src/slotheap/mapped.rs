@@ -0,0 +1,451 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr::{self, NonNull},
+};
+
+use crate::{
+    inner,
+    util::{RwLockReadGuard, RwLockWriteGuard},
+    SlotHeapPeek, SlotHeapPeekMut, SlotHeapRef, SlotHeapRefMut,
+};
+
+/// Immutable reference to a part of an element in a [`SlotHeap`](crate::SlotHeap), holding a
+/// read lock.
+///
+/// Created by [`SlotHeapRef::map`], [`SlotHeapPeek::map`] and friends.
+pub struct MappedSlotHeapRef<'a, T, U>
+where
+    T: PartialOrd,
+    U: ?Sized,
+{
+    _guard: RwLockReadGuard<'a, inner::SlotHeap<T>>,
+    value: NonNull<U>,
+}
+
+#[reflica::reflica]
+impl<T, U> MappedSlotHeapRef<'_, T, U>
+where
+    T: PartialOrd,
+    U: ?Sized,
+{
+    fn deref(&self) -> &U {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+/// Mutable reference to a part of an element in a [`SlotHeap`](crate::SlotHeap), holding a
+/// write lock.
+///
+/// The element is always re-heapified on drop of the returned guard, since the projection
+/// had mutable access to it.
+///
+/// Created by [`SlotHeapRefMut::map`], [`SlotHeapPeekMut::map`] and friends.
+///
+/// Like a `&mut U`, it is invariant in `U`, so a projection cannot be shortened to a type that
+/// would let a shorter-lived value be written into the heap:
+///
+/// ```compile_fail
+/// use deadlock::MappedSlotHeapRefMut;
+///
+/// fn shorten<'a, 'b, T: PartialOrd>(
+///     r: MappedSlotHeapRefMut<'a, T, &'static str>,
+/// ) -> MappedSlotHeapRefMut<'a, T, &'b str> {
+///     r
+/// }
+/// ```
+pub struct MappedSlotHeapRefMut<'a, T, U>
+where
+    T: PartialOrd,
+    U: ?Sized,
+{
+    guard: RwLockWriteGuard<'a, inner::SlotHeap<T>>,
+    id: usize,
+    value: NonNull<U>,
+    _marker: PhantomData<&'a mut U>,
+}
+
+#[reflica::reflica]
+impl<T, U> MappedSlotHeapRefMut<'_, T, U>
+where
+    T: PartialOrd,
+    U: ?Sized,
+{
+    fn deref(&self) -> &U {
+        unsafe { self.value.as_ref() }
+    }
+
+    fn deref_mut(&mut self) -> &mut U {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T, U> Drop for MappedSlotHeapRefMut<'_, T, U>
+where
+    T: PartialOrd,
+    U: ?Sized,
+{
+    fn drop(&mut self) {
+        let index = unsafe { self.guard.get_unchecked_index(self.id) };
+        unsafe {
+            self.guard.heapify(index);
+        }
+    }
+}
+
+impl<'a, T> SlotHeapPeek<'a, T>
+where
+    T: PartialOrd,
+{
+    /// Projects the ref to a part of the minimum element, keeping the read lock.
+    ///
+    /// This is an associated function so that it does not shadow a method of `T`.
+    pub fn map<U, F>(r: Self, f: F) -> MappedSlotHeapRef<'a, T, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> &U,
+    {
+        let value = NonNull::from(f(&r));
+        MappedSlotHeapRef {
+            _guard: r.guard,
+            value,
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<U, E, F>(r: Self, f: F) -> Result<MappedSlotHeapRef<'a, T, U>, (Self, E)>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> Result<&U, E>,
+    {
+        match f(&r).map(NonNull::from) {
+            Ok(value) => Ok(MappedSlotHeapRef {
+                _guard: r.guard,
+                value,
+            }),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<U, F>(r: Self, f: F) -> Result<MappedSlotHeapRef<'a, T, U>, Self>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        match f(&r).map(NonNull::from) {
+            Some(value) => Ok(MappedSlotHeapRef {
+                _guard: r.guard,
+                value,
+            }),
+            None => Err(r),
+        }
+    }
+}
+
+impl<'a, T> SlotHeapRef<'a, T>
+where
+    T: PartialOrd,
+{
+    /// Projects the ref to a part of the element, keeping the read lock.
+    ///
+    /// This is an associated function so that it does not shadow a method of `T`.
+    pub fn map<U, F>(r: Self, f: F) -> MappedSlotHeapRef<'a, T, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> &U,
+    {
+        let value = NonNull::from(f(&r));
+        MappedSlotHeapRef {
+            _guard: r.guard,
+            value,
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<U, E, F>(r: Self, f: F) -> Result<MappedSlotHeapRef<'a, T, U>, (Self, E)>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> Result<&U, E>,
+    {
+        match f(&r).map(NonNull::from) {
+            Ok(value) => Ok(MappedSlotHeapRef {
+                _guard: r.guard,
+                value,
+            }),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<U, F>(r: Self, f: F) -> Result<MappedSlotHeapRef<'a, T, U>, Self>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        match f(&r).map(NonNull::from) {
+            Some(value) => Ok(MappedSlotHeapRef {
+                _guard: r.guard,
+                value,
+            }),
+            None => Err(r),
+        }
+    }
+}
+
+impl<'a, T> SlotHeapPeekMut<'a, T>
+where
+    T: PartialOrd,
+{
+    /// Projects the ref to a part of the minimum element, keeping the write lock.
+    ///
+    /// This is an associated function so that it does not shadow a method of `T`.
+    pub fn map<U, F>(mut r: Self, f: F) -> MappedSlotHeapRefMut<'a, T, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let value = NonNull::from(f(&mut r));
+        Self::into_mapped(r, value)
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<U, E, F>(mut r: Self, f: F) -> Result<MappedSlotHeapRefMut<'a, T, U>, (Self, E)>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> Result<&mut U, E>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Ok(value) => Ok(Self::into_mapped(r, value)),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<U, F>(mut r: Self, f: F) -> Result<MappedSlotHeapRefMut<'a, T, U>, Self>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Some(value) => Ok(Self::into_mapped(r, value)),
+            None => Err(r),
+        }
+    }
+
+    fn into_mapped<U>(r: Self, value: NonNull<U>) -> MappedSlotHeapRefMut<'a, T, U>
+    where
+        U: ?Sized,
+    {
+        let this = ManuallyDrop::new(r);
+        MappedSlotHeapRefMut {
+            guard: unsafe { ptr::read(&this.guard) },
+            id: unsafe { this.guard.peek_unchecked_id() },
+            value,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> SlotHeapRefMut<'a, T>
+where
+    T: PartialOrd,
+{
+    /// Projects the ref to a part of the element, keeping the write lock.
+    ///
+    /// This is an associated function so that it does not shadow a method of `T`.
+    pub fn map<U, F>(mut r: Self, f: F) -> MappedSlotHeapRefMut<'a, T, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let value = NonNull::from(f(&mut r));
+        Self::into_mapped(r, value)
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<U, E, F>(mut r: Self, f: F) -> Result<MappedSlotHeapRefMut<'a, T, U>, (Self, E)>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> Result<&mut U, E>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Ok(value) => Ok(Self::into_mapped(r, value)),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<U, F>(mut r: Self, f: F) -> Result<MappedSlotHeapRefMut<'a, T, U>, Self>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Some(value) => Ok(Self::into_mapped(r, value)),
+            None => Err(r),
+        }
+    }
+
+    fn into_mapped<U>(r: Self, value: NonNull<U>) -> MappedSlotHeapRefMut<'a, T, U>
+    where
+        U: ?Sized,
+    {
+        let this = ManuallyDrop::new(r);
+        MappedSlotHeapRefMut {
+            guard: unsafe { ptr::read(&this.guard) },
+            id: this.id,
+            value,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, U> MappedSlotHeapRef<'a, T, U>
+where
+    T: PartialOrd,
+    U: ?Sized,
+{
+    /// Projects the ref further, keeping the read lock.
+    pub fn map<V, F>(r: Self, f: F) -> MappedSlotHeapRef<'a, T, V>
+    where
+        V: ?Sized,
+        F: FnOnce(&U) -> &V,
+    {
+        let value = NonNull::from(f(&r));
+        MappedSlotHeapRef {
+            _guard: r._guard,
+            value,
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<V, E, F>(r: Self, f: F) -> Result<MappedSlotHeapRef<'a, T, V>, (Self, E)>
+    where
+        V: ?Sized,
+        F: FnOnce(&U) -> Result<&V, E>,
+    {
+        match f(&r).map(NonNull::from) {
+            Ok(value) => Ok(MappedSlotHeapRef {
+                _guard: r._guard,
+                value,
+            }),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<V, F>(r: Self, f: F) -> Result<MappedSlotHeapRef<'a, T, V>, Self>
+    where
+        V: ?Sized,
+        F: FnOnce(&U) -> Option<&V>,
+    {
+        match f(&r).map(NonNull::from) {
+            Some(value) => Ok(MappedSlotHeapRef {
+                _guard: r._guard,
+                value,
+            }),
+            None => Err(r),
+        }
+    }
+}
+
+impl<'a, T, U> MappedSlotHeapRefMut<'a, T, U>
+where
+    T: PartialOrd,
+    U: ?Sized,
+{
+    /// Projects the ref further, keeping the write lock.
+    pub fn map<V, F>(mut r: Self, f: F) -> MappedSlotHeapRefMut<'a, T, V>
+    where
+        V: ?Sized,
+        F: FnOnce(&mut U) -> &mut V,
+    {
+        let value = NonNull::from(f(&mut r));
+        Self::remap(r, value)
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<V, E, F>(mut r: Self, f: F) -> Result<MappedSlotHeapRefMut<'a, T, V>, (Self, E)>
+    where
+        V: ?Sized,
+        F: FnOnce(&mut U) -> Result<&mut V, E>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Ok(value) => Ok(Self::remap(r, value)),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<V, F>(mut r: Self, f: F) -> Result<MappedSlotHeapRefMut<'a, T, V>, Self>
+    where
+        V: ?Sized,
+        F: FnOnce(&mut U) -> Option<&mut V>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Some(value) => Ok(Self::remap(r, value)),
+            None => Err(r),
+        }
+    }
+
+    fn remap<V>(r: Self, value: NonNull<V>) -> MappedSlotHeapRefMut<'a, T, V>
+    where
+        V: ?Sized,
+    {
+        let this = ManuallyDrop::new(r);
+        MappedSlotHeapRefMut {
+            guard: unsafe { ptr::read(&this.guard) },
+            id: this.id,
+            value,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, U> fmt::Debug for MappedSlotHeapRef<'_, T, U>
+where
+    T: PartialOrd,
+    U: ?Sized + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T, U> fmt::Debug for MappedSlotHeapRefMut<'_, T, U>
+where
+    T: PartialOrd,
+    U: ?Sized + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+unsafe impl<T, U> Send for MappedSlotHeapRef<'_, T, U>
+where
+    T: Send + Sync + PartialOrd,
+    U: ?Sized + Sync,
+{
+}
+unsafe impl<T, U> Sync for MappedSlotHeapRef<'_, T, U>
+where
+    T: Send + Sync + PartialOrd,
+    U: ?Sized + Sync,
+{
+}
+
+unsafe impl<T, U> Send for MappedSlotHeapRefMut<'_, T, U>
+where
+    T: Send + PartialOrd,
+    U: ?Sized + Send,
+{
+}
+unsafe impl<T, U> Sync for MappedSlotHeapRefMut<'_, T, U>
+where
+    T: Send + Sync + PartialOrd,
+    U: ?Sized + Sync,
+{
+}
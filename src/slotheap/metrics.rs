@@ -0,0 +1,50 @@
+use crate::{LockStats, SlotHeap};
+
+impl<T> SlotHeap<T>
+where
+    T: PartialOrd,
+{
+    /// Returns a snapshot of the occupancy, sift and lock counters of the heap.
+    ///
+    /// The lock counters are read before the heap is read-locked, so taking a snapshot shows up
+    /// in the next one.
+    ///
+    /// Time complexity: O(1)
+    pub fn stats(&self) -> SlotHeapStats {
+        let lock = unsafe { self.inner.raw() }.stats();
+        let guard = self.inner.read();
+        SlotHeapStats {
+            len: guard.len(),
+            capacity: guard.capacity(),
+            inserts: guard.inserts(),
+            removes: guard.removes(),
+            sift_ups: guard.sift_ups(),
+            sift_downs: guard.sift_downs(),
+            comparisons: guard.comparisons(),
+            lock,
+        }
+    }
+}
+
+/// Occupancy, sift and lock counters of a [`SlotHeap`].
+///
+/// Created by [`SlotHeap::stats`]. Counters are cumulative since the heap was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlotHeapStats {
+    /// Number of elements in the heap.
+    pub len: usize,
+    /// Number of elements the heap can hold without reallocating.
+    pub capacity: usize,
+    /// Number of elements ever inserted into the heap.
+    pub inserts: u64,
+    /// Number of elements ever removed from the heap.
+    pub removes: u64,
+    /// Number of swaps made while sifting elements towards the top.
+    pub sift_ups: u64,
+    /// Number of swaps made while sifting elements towards the bottom.
+    pub sift_downs: u64,
+    /// Number of element comparisons.
+    pub comparisons: u64,
+    /// Counters of the heap lock.
+    pub lock: LockStats,
+}
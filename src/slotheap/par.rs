@@ -0,0 +1,75 @@
+use rayon::prelude::*;
+use std::fmt;
+use triomphe::Arc;
+
+use crate::{
+    inner,
+    util::{self, RwLockReadGuard},
+    SlotHeap,
+};
+
+impl<T> SlotHeap<T>
+where
+    T: PartialOrd + Send + Sync,
+{
+    /// Creates a parallel iterator over immutable references to elements in heap order.
+    ///
+    /// The heap is read-locked once, and the lock is held until the iterator and every ref
+    /// yielded from it are dropped.
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = SlotHeapParRef<'_, T>> {
+        let guard = Arc::new(ParGuard(util::read_shared(&self.inner)));
+        (0..guard.0.len()).into_par_iter().map(move |index| {
+            let id = unsafe { guard.0.get_unchecked_nth_id(index) };
+            SlotHeapParRef {
+                guard: guard.clone(),
+                id,
+            }
+        })
+    }
+}
+
+struct ParGuard<'a, T>(RwLockReadGuard<'a, inner::SlotHeap<T>>)
+where
+    T: PartialOrd;
+
+/// Immutable reference to an element in a [`SlotHeap`], sharing the read lock with the other
+/// refs yielded by [`SlotHeap::par_iter`].
+pub struct SlotHeapParRef<'a, T>
+where
+    T: PartialOrd,
+{
+    guard: Arc<ParGuard<'a, T>>,
+    id: usize,
+}
+
+#[reflica::reflica]
+impl<T> SlotHeapParRef<'_, T>
+where
+    T: PartialOrd,
+{
+    /// Returns whether this element is the current minimum (top) of the heap.
+    ///
+    /// Time complexity: O(1)
+    pub fn is_top(&self) -> bool {
+        unsafe { self.guard.0.get_unchecked_index(self.id) == 0 }
+    }
+
+    fn deref(&self) -> &T {
+        unsafe { self.guard.0.get_unchecked(self.id) }
+    }
+}
+
+impl<T> fmt::Debug for SlotHeapParRef<'_, T>
+where
+    T: PartialOrd + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+unsafe impl<T> Send for ParGuard<'_, T> where T: Send + Sync + PartialOrd {}
+unsafe impl<T> Sync for ParGuard<'_, T> where T: Send + Sync + PartialOrd {}
+
+unsafe impl<T> Send for SlotHeapParRef<'_, T> where T: Send + Sync + PartialOrd {}
+unsafe impl<T> Sync for SlotHeapParRef<'_, T> where T: Send + Sync + PartialOrd {}
@@ -0,0 +1,142 @@
+use serde::{
+    de::{Error, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{collections::HashMap, fmt, marker::PhantomData, mem::ManuallyDrop};
+
+use crate::{slotmap::cautious, SlotHeap, SlotHeapId, SlotHeapKey};
+
+impl<T> Serialize for SlotHeap<T>
+where
+    T: PartialOrd + Serialize,
+{
+    /// Serializes the heap under a read lock as a sequence of `(key, value)` pairs in heap order.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let guard = self.inner.read();
+        serializer.collect_seq((0..guard.len()).map(|index| {
+            let id = unsafe { guard.get_unchecked_nth_id(index) };
+            let generation = unsafe { guard.get_unchecked_generation(id) };
+            (SlotHeapKey::new(id, generation), unsafe {
+                guard.get_unchecked(id)
+            })
+        }))
+    }
+}
+
+impl<T> SlotHeap<T>
+where
+    T: PartialOrd,
+{
+    /// Inserts the values serialized from another heap and returns their new handles keyed by
+    /// their old keys.
+    pub fn deserialize_into<'de, D>(
+        &self,
+        deserializer: D,
+    ) -> Result<HashMap<SlotHeapKey, SlotHeapId<T>>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(Entries {
+            heap: self,
+            marker: PhantomData,
+        })
+    }
+}
+
+/// A freshly deserialized [`SlotHeap`] together with the handles of its elements, keyed by the
+/// keys they were serialized with.
+pub struct SlotHeapRestore<T>
+where
+    T: PartialOrd,
+{
+    heap: SlotHeap<T>,
+    ids: HashMap<SlotHeapKey, SlotHeapId<T>>,
+}
+
+impl<T> SlotHeapRestore<T>
+where
+    T: PartialOrd,
+{
+    /// Returns the restored heap and the handles keyed by their old keys.
+    pub fn into_parts(self) -> (SlotHeap<T>, HashMap<SlotHeapKey, SlotHeapId<T>>) {
+        (self.heap, self.ids)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for SlotHeapRestore<T>
+where
+    T: PartialOrd + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let heap = SlotHeap::new();
+        let ids = heap.deserialize_into(deserializer)?;
+        Ok(Self { heap, ids })
+    }
+}
+
+struct Entries<'a, T>
+where
+    T: PartialOrd,
+{
+    heap: &'a SlotHeap<T>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'de, T> Visitor<'de> for Entries<'_, T>
+where
+    T: PartialOrd + Deserialize<'de>,
+{
+    type Value = HashMap<SlotHeapKey, SlotHeapId<T>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of (key, value) pairs")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(cautious(seq.size_hint()));
+
+        while let Some(entry) = seq.next_element::<(SlotHeapKey, T)>()? {
+            entries.push(entry)
+        }
+
+        let mut guard = self.heap.inner.write();
+        guard.reserve(entries.len());
+        let ids = entries
+            .into_iter()
+            .map(|(key, value)| {
+                let (id, _) = guard.insert(value);
+                let generation = unsafe { guard.get_unchecked_generation(id) };
+                let from = ManuallyDrop::new(self.heap.inner.clone());
+                (
+                    key,
+                    SlotHeapId {
+                        from,
+                        id,
+                        generation,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        drop(guard);
+
+        let mut by_key = HashMap::with_capacity(ids.len());
+
+        for (key, id) in ids {
+            if by_key.insert(key, id).is_some() {
+                return Err(A::Error::custom(format_args!("duplicate key {:?}", key)));
+            }
+        }
+
+        Ok(by_key)
+    }
+}
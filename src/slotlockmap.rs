@@ -0,0 +1,177 @@
+//! Thread-safe slot map with a lock per entry and stable RAII handle.
+
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::fmt;
+
+use crate::{SlotMap, SlotMapBuilder, SlotMapBuilderError, SlotMapId};
+
+/// Thread-safe slot map with a lock per entry and stable RAII handle.
+///
+/// Unlike [`SlotMap`], where [`SlotMapId::get_mut`] write-locks a whole shard, each entry here
+/// has its own lock and lives in its own allocation. The shard lock is only held briefly while
+/// locating an entry and during insert and remove, so long-held guards on independent entries
+/// do not block each other.
+///
+/// Stores values in slots and returns [`SlotLockMapId`].
+pub struct SlotLockMap<T> {
+    map: SlotMap<Box<RwLock<T>>>,
+}
+
+impl<T> SlotLockMap<T> {
+    /// Creates a new slot map with a default number of shards (derived from parallelism).
+    ///
+    /// Use [`SlotMapBuilder::build_locked`] to configure the shards.
+    pub fn new() -> Self {
+        Self {
+            map: SlotMap::new(),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// Time complexity: O(# of shards)
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns whether the map is empty.
+    ///
+    /// Time complexity: O(# of shards)
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Inserts a value and returns its handle.
+    ///
+    /// Time complexity: O(1)
+    pub fn insert(&self, value: T) -> SlotLockMapId<T> {
+        SlotLockMapId {
+            id: self.map.insert(Box::new(RwLock::new(value))),
+        }
+    }
+}
+
+impl<T> Default for SlotLockMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for SlotLockMap<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotLockMap")
+            .field("map", &self.map)
+            .finish()
+    }
+}
+
+impl SlotMapBuilder {
+    /// Validates the configuration and creates a slot map with a lock per entry.
+    pub fn build_locked<T>(self) -> Result<SlotLockMap<T>, SlotMapBuilderError> {
+        self.build().map(|map| SlotLockMap { map })
+    }
+}
+
+/// Stable RAII handle to a value in a [`SlotLockMap`].
+///
+/// Dropping it removes the value from the map.
+pub struct SlotLockMapId<T> {
+    id: SlotMapId<Box<RwLock<T>>>,
+}
+
+impl<T> SlotLockMapId<T> {
+    /// Takes the value out of the map with consuming self.
+    ///
+    /// Time complexity: O(1)
+    pub fn into_inner(self) -> T {
+        self.id.into_inner().into_inner()
+    }
+
+    /// Returns an immutable reference to the value, holding the entry's read lock until the ref
+    /// is dropped.
+    ///
+    /// Time complexity: O(1)
+    pub fn get(&self) -> SlotLockMapRef<'_, T> {
+        SlotLockMapRef {
+            guard: self.lock().read(),
+        }
+    }
+
+    /// Returns a mutable reference to the value, holding the entry's write lock until the ref is
+    /// dropped.
+    ///
+    /// Time complexity: O(1)
+    pub fn get_mut(&self) -> SlotLockMapRefMut<'_, T> {
+        SlotLockMapRefMut {
+            guard: self.lock().write(),
+        }
+    }
+
+    fn lock(&self) -> &RwLock<T> {
+        let lock = &**self.id.get() as *const RwLock<T>;
+        unsafe { &*lock }
+    }
+}
+
+impl<T> fmt::Debug for SlotLockMapId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotLockMapId")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// Immutable reference to a value in a [`SlotLockMap`], holding the entry's read lock.
+pub struct SlotLockMapRef<'a, T> {
+    guard: RwLockReadGuard<'a, T>,
+}
+
+#[reflica::reflica]
+impl<T> SlotLockMapRef<'_, T> {
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+/// Mutable reference to a value in a [`SlotLockMap`], holding the entry's write lock.
+pub struct SlotLockMapRefMut<'a, T> {
+    guard: RwLockWriteGuard<'a, T>,
+}
+
+#[reflica::reflica]
+impl<T> SlotLockMapRefMut<'_, T> {
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> fmt::Debug for SlotLockMapRef<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> fmt::Debug for SlotLockMapRefMut<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+unsafe impl<T> Send for SlotLockMapRef<'_, T> where T: Send + Sync {}
+unsafe impl<T> Sync for SlotLockMapRef<'_, T> where T: Send + Sync {}
+
+unsafe impl<T> Send for SlotLockMapRefMut<'_, T> where T: Send {}
+unsafe impl<T> Sync for SlotLockMapRefMut<'_, T> where T: Send + Sync {}
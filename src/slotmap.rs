@@ -1,33 +1,102 @@
 //! Thread-safe slot map with stable RAII handle.
 
-use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use std::{
-    fmt, iter,
+    cmp, error, fmt,
+    hash::{Hash, Hasher},
+    iter,
     mem::{self, ManuallyDrop},
+    ops::Deref,
+    slice,
     sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
 };
 use triomphe::Arc;
 
-use crate::{inner, util};
+use crate::{
+    inner,
+    util::{self, RwLock, RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard},
+};
+
+mod builder;
+mod key;
+mod many;
+mod mapped;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "rayon")]
+mod par;
+mod raw;
+mod selector;
+#[cfg(feature = "serde")]
+mod serde;
+mod snapshot;
+
+#[cfg(feature = "serde")]
+pub use self::serde::*;
+pub use builder::*;
+pub use key::*;
+pub use many::*;
+pub use mapped::*;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+#[cfg(feature = "rayon")]
+pub use par::*;
+pub use raw::*;
+pub use selector::*;
+pub use snapshot::*;
+
+static NEXT_MAP: AtomicUsize = AtomicUsize::new(0);
+
+const EVICTED: &str = "value was evicted from the map";
 
 /// Thread-safe slot map with stable RAII handle.
 ///
 /// Stores values in slots and returns [`SlotMapId`].
 pub struct SlotMap<T> {
     shards: Box<[Arc<Shard<T>>]>,
-    rr: AtomicUsize,
+    selector: Box<dyn ShardSelector>,
 }
 
 struct Shard<T> {
     inner: RwLock<inner::SlotMap<T>>,
     len: AtomicUsize,
+    map: usize,
+    index: usize,
+}
+
+impl<T> Shard<T> {
+    /// Key of the global lock order: by map, then by shard index.
+    fn lock_order(&self) -> (usize, usize) {
+        (self.map, self.index)
+    }
 }
 
 impl<T> SlotMap<T> {
     /// Creates a new slot map with a default number of shards (derived from parallelism).
+    ///
+    /// Use [`SlotMapBuilder`] to configure the shard count, capacity and [`ShardSelector`].
     pub fn new() -> Self {
-        let num_shards = util::default_num_shards();
-        unsafe { Self::new_unchecked(num_shards) }
+        Self::with_capacity(0)
+    }
+
+    /// Creates a new slot map with a default number of shards, pre-allocating room for at least
+    /// `capacity` values split evenly between the shards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity overflows, and aborts like [`Vec::reserve`] if the allocation fails.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let num_shards = util::default_num_shards().min(raw::MAX_SHARDS);
+        let per_shard = capacity.div_ceil(num_shards);
+        let inners = iter::repeat_with(|| inner::SlotMap::with_capacity(per_shard))
+            .take(num_shards)
+            .collect();
+        unsafe { Self::from_inners_unchecked(inners, Box::new(RoundRobin::default())) }
+    }
+
+    /// Returns the number of shards.
+    pub fn num_shards(&self) -> usize {
+        self.shards.len()
     }
 
     /// Returns the number of entries in the map.
@@ -49,40 +118,218 @@ impl<T> SlotMap<T> {
             .all(|shard| shard.len.load(Ordering::Relaxed) == 0)
     }
 
+    /// Returns whether `id` is a handle of this map whose value has not been evicted.
+    ///
+    /// Time complexity: O(1)
+    pub fn contains(&self, id: &SlotMapId<T>) -> bool {
+        self.shards
+            .get(id.from.index)
+            .filter(|shard| Arc::ptr_eq(shard, &id.from))
+            .is_some_and(|shard| shard.inner.read().contains(id.id, id.generation))
+    }
+
+    /// Returns the number of values the map can hold without reallocating, summed over shards.
+    ///
+    /// Time complexity: O(# of shards)
+    pub fn capacity(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.inner.read().capacity())
+            .sum()
+    }
+
+    /// Reserves room for at least `additional` more values, split evenly between the shards like
+    /// [`with_capacity`](Self::with_capacity).
+    ///
+    /// Acquires each shard's write lock in turn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity overflows, and aborts like [`Vec::reserve`] if the allocation fails.
+    ///
+    /// Time complexity: O(capacity)
+    pub fn reserve(&self, additional: usize) {
+        let per_shard = additional.div_ceil(self.shards.len());
+
+        for shard in self.shards.iter() {
+            shard.inner.write().reserve(per_shard)
+        }
+    }
+
     /// Inserts a value and returns its handle.
     ///
     /// Time complexity: O(1)
     pub fn insert(&self, value: T) -> SlotMapId<T> {
-        let shard_index = self.select_shard();
-        let shard = unsafe { self.shards.get_unchecked(shard_index) };
-        let from = ManuallyDrop::new(shard.clone());
+        let (from, id, generation) = self.insert_entry(value, 0);
+        SlotMapId {
+            from,
+            id,
+            generation,
+        }
+    }
 
-        let mut guard = shard.inner.write();
-        let id = guard.insert(value);
-        shard.len.fetch_add(1, Ordering::Relaxed);
+    /// Inserts a value and returns a reference-counted handle to it.
+    ///
+    /// The value is removed when the last clone of the handle is dropped.
+    ///
+    /// Time complexity: O(1)
+    pub fn insert_shared(&self, value: T) -> SlotMapSharedId<T> {
+        let (from, id, generation) = self.insert_entry(value, 1);
+        SlotMapSharedId {
+            from,
+            id,
+            generation,
+        }
+    }
 
-        SlotMapId { from, id }
+    /// Inserts all values and returns their handles in iteration order.
+    ///
+    /// Values are distributed across shards up front, and each shard receives its batch under a
+    /// single write lock acquisition with capacity reserved in advance.
+    ///
+    /// Time complexity: O(n)
+    pub fn insert_many<I>(&self, values: I) -> Vec<SlotMapId<T>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut ids = Vec::new();
+        self.insert_many_into(values, &mut ids);
+        ids
     }
 
-    /// Creates an iterator over immutable references to values in the map.
+    /// Inserts all values like [`insert_many`](Self::insert_many), appending their handles to
+    /// `ids` in iteration order.
+    ///
+    /// Time complexity: O(n)
+    pub fn insert_many_into<I>(&self, values: I, ids: &mut Vec<SlotMapId<T>>)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut pending = vec![0; self.shards.len()];
+        let values = values
+            .into_iter()
+            .map(|value| {
+                let shard_index = self.select_shard(&pending);
+                pending[shard_index] += 1;
+                (shard_index, value)
+            })
+            .collect::<Vec<_>>();
+        let mut batches = pending
+            .iter()
+            .map(|&count| Vec::with_capacity(count))
+            .collect::<Vec<_>>();
+        let mut slots = Vec::with_capacity(values.len());
+
+        for (position, (shard_index, value)) in values.into_iter().enumerate() {
+            batches[shard_index].push((position, value));
+            slots.push((shard_index, 0, 0))
+        }
+
+        for (shard_index, batch) in batches.into_iter().enumerate() {
+            if batch.is_empty() {
+                continue;
+            }
+
+            let shard = unsafe { self.shards.get_unchecked(shard_index) };
+            let mut guard = shard.inner.write();
+            guard.reserve(batch.len());
+            shard.len.fetch_add(batch.len(), Ordering::Relaxed);
+
+            for (position, value) in batch {
+                let id = guard.insert(value);
+                let generation = unsafe { guard.get_unchecked_generation(id) };
+                slots[position] = (shard_index, id, generation)
+            }
+        }
+
+        ids.extend(
+            slots
+                .into_iter()
+                .map(|(shard_index, id, generation)| SlotMapId {
+                    from: ManuallyDrop::new(
+                        unsafe { self.shards.get_unchecked(shard_index) }.clone(),
+                    ),
+                    id,
+                    generation,
+                }),
+        )
+    }
+
+    /// Evicts every value for which `f` returns `false`.
+    ///
+    /// Handles to evicted values stay valid objects: their checked accessors return `None` and
+    /// dropping them does nothing. Evicted values are dropped after all locks are released.
+    ///
+    /// Time complexity: O(n)
+    pub fn retain<F>(&self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        drop(self.remove_where(|value| !f(value)))
+    }
+
+    /// Evicts every value for which `f` returns `true` and returns them.
+    ///
+    /// Each shard is write-locked once. Handles to evicted values behave as described in
+    /// [`retain`](Self::retain).
+    ///
+    /// Time complexity: O(n)
+    pub fn remove_where<F>(&self, mut f: F) -> Vec<T>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let mut removed = Vec::new();
+
+        for shard in self.shards.iter() {
+            let mut guard = shard.inner.write();
+
+            for index in (0..guard.len()).rev() {
+                let id = unsafe { guard.get_unchecked_nth_id(index) };
+
+                if f(unsafe { guard.get_unchecked_mut(id) }) {
+                    removed.push(unsafe { guard.remove_unchecked(id) });
+                    shard.len.fetch_sub(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Evicts every value in the map.
+    ///
+    /// Handles to evicted values behave as described in [`retain`](Self::retain).
+    ///
+    /// Time complexity: O(n)
+    pub fn clear(&self) {
+        self.retain(|_| false)
+    }
+
+    /// Creates an iterator over the keys of and immutable references to values in the map.
     ///
     /// Each call to `next()` acquires and releases a read lock for each individual element.
+    /// Creating the iterator briefly read-locks every shard to record its capacity; see
+    /// [`SlotMapIter`] for the behavior under concurrent insertion and removal.
     pub fn iter(&self) -> SlotMapIter<'_, T> {
         SlotMapIter {
             shards: &self.shards,
+            capacities: self.capacities(),
             shard_index: 0,
-            inner_index: 0,
+            slot: 0,
         }
     }
 
-    /// Creates an iterator over mutable references to values in the map.
+    /// Creates an iterator over the keys of and mutable references to values in the map.
     ///
     /// Each call to `next()` acquires and releases a write lock for each individual element.
+    /// Creating the iterator briefly read-locks every shard to record its capacity; see
+    /// [`SlotMapIter`] for the behavior under concurrent insertion and removal.
     pub fn iter_mut(&self) -> SlotMapIterMut<'_, T> {
         SlotMapIterMut {
             shards: &self.shards,
+            capacities: self.capacities(),
             shard_index: 0,
-            inner_index: 0,
+            slot: 0,
         }
     }
 
@@ -92,44 +339,94 @@ impl<T> SlotMap<T> {
     /// each [`SlotMapShardRef`] holds its read lock for the lifetime of the shard reference.
     /// This is more efficient when all values in a shard need to be processed at once.
     pub fn shards(&self) -> impl Iterator<Item = SlotMapShardRef<'_, T>> {
-        self.shards.iter().map(|shard| SlotMapShardRef {
+        self.shards_in_lock_order().map(|shard| SlotMapShardRef {
             guard: shard.inner.read(),
+            shard: shard.index,
+        })
+    }
+
+    /// Returns an iterator over mutable shard references, each holding a write lock for an
+    /// entire shard.
+    ///
+    /// Unlike [`iter_mut`](Self::iter_mut), which acquires and releases a lock per element,
+    /// each [`SlotMapShardRefMut`] holds its write lock for the lifetime of the shard reference.
+    /// Shards are yielded in the global lock order, so holding several at once cannot deadlock
+    /// against other operations that lock more than one shard.
+    pub fn shards_mut(&self) -> impl Iterator<Item = SlotMapShardRefMut<'_, T>> {
+        self.shards_in_lock_order().map(|shard| SlotMapShardRefMut {
+            guard: shard.inner.write(),
+            shard: shard.index,
         })
     }
 
-    unsafe fn new_unchecked(num_shards: usize) -> Self {
+    /// `inners` must be non-empty and hold at most `raw::MAX_SHARDS` shards.
+    pub(crate) unsafe fn from_inners_unchecked(
+        inners: Vec<inner::SlotMap<T>>,
+        selector: Box<dyn ShardSelector>,
+    ) -> Self {
+        let map = NEXT_MAP.fetch_add(1, Ordering::Relaxed);
         Self {
-            shards: iter::repeat_with(|| {
-                Arc::new(Shard {
-                    inner: RwLock::new(inner::SlotMap::new()),
-                    len: 0.into(),
+            shards: inners
+                .into_iter()
+                .enumerate()
+                .map(|(index, inner)| {
+                    Arc::new(Shard {
+                        inner: util::shard_lock(index, inner),
+                        len: 0.into(),
+                        map,
+                        index,
+                    })
                 })
-            })
-            .take(num_shards)
-            .collect(),
-            rr: 0.into(),
+                .collect(),
+            selector,
         }
     }
 
-    fn select_shard(&self) -> usize {
-        let rr = self.rr.fetch_add(1, Ordering::Relaxed);
-        let candidates = (0..4).map(|i| {
-            let index = (rr + i * self.rr_interval()) & self.rr_mask();
-            let len = unsafe { self.shards.get_unchecked(index) }
-                .len
-                .load(Ordering::Relaxed);
-            (index, len)
-        });
-        let min = candidates.min_by_key(|(_, len)| *len);
-        unsafe { min.unwrap_unchecked() }.0
+    fn insert_entry(&self, value: T, refs: usize) -> (ManuallyDrop<Arc<Shard<T>>>, usize, u32) {
+        let shard_index = self.select_shard(&[]);
+        let shard = unsafe { self.shards.get_unchecked(shard_index) };
+        let from = ManuallyDrop::new(shard.clone());
+
+        let mut guard = shard.inner.write();
+        let id = guard.insert(value);
+        let generation = unsafe { guard.get_unchecked_generation(id) };
+        unsafe { guard.get_unchecked_refs(id) }.store(refs, Ordering::Relaxed);
+        shard.len.fetch_add(1, Ordering::Relaxed);
+
+        (from, id, generation)
+    }
+
+    fn capacities(&self) -> Box<[usize]> {
+        self.shards
+            .iter()
+            .map(|shard| shard.inner.read().capacity())
+            .collect()
     }
 
-    fn rr_mask(&self) -> usize {
-        self.shards.len() - 1
+    /// Shards in the global lock order: by map, then by shard index.
+    ///
+    /// Every operation that holds more than one shard lock at a time acquires them in this order,
+    /// so no two of them can deadlock against each other. Within one map it is iteration order.
+    fn shards_in_lock_order(&self) -> slice::Iter<'_, Arc<Shard<T>>> {
+        self.shards.iter()
     }
 
-    fn rr_interval(&self) -> usize {
-        self.shards.len() >> 2
+    fn select_shard(&self, pending: &[usize]) -> usize {
+        let load = |index: usize| {
+            unsafe { self.shards.get_unchecked(index) }
+                .len
+                .load(Ordering::Relaxed)
+                + pending.get(index).copied().unwrap_or(0)
+        };
+        let index = self
+            .selector
+            .select(&ShardLoads::new(self.shards.len(), &load));
+        assert!(
+            index < self.shards.len(),
+            "shard selector returned out-of-range index {}",
+            index
+        );
+        index
     }
 }
 
@@ -142,61 +439,590 @@ impl<T> Default for SlotMap<T> {
 /// Stable RAII handle to a value in a [`SlotMap`].
 ///
 /// Dropping it removes the value from the map.
+///
+/// Handles compare, hash and order by map, shard, slot and generation. Maps are ordered by
+/// creation.
 pub struct SlotMapId<T> {
     from: ManuallyDrop<Arc<Shard<T>>>,
     id: usize,
+    generation: u32,
 }
 
 impl<T> SlotMapId<T> {
     /// Takes the value out of the map with consuming self.
     ///
+    /// # Panics
+    ///
+    /// Panics if the value was evicted from the map.
+    ///
     /// Time complexity: O(1)
-    pub fn into_inner(mut self) -> T {
-        let mut guard = self.from.inner.write();
-        let value = unsafe { guard.remove_unchecked(self.id) };
-        self.from.len.fetch_sub(1, Ordering::Relaxed);
+    pub fn into_inner(self) -> T {
+        self.try_into_inner().expect(EVICTED)
+    }
+
+    /// Takes the value out of the map with consuming self, or returns `None` if the value was
+    /// evicted from the map.
+    ///
+    /// Time complexity: O(1)
+    pub fn try_into_inner(mut self) -> Option<T> {
+        let mut guard = self.lock_write();
+        let value = guard.contains(self.id, self.generation).then(|| {
+            self.from.len.fetch_sub(1, Ordering::Relaxed);
+            unsafe { guard.remove_unchecked(self.id) }
+        });
         drop(guard);
         unsafe { ManuallyDrop::drop(&mut self.from) };
         mem::forget(self);
         value
     }
 
+    /// Moves the value into `map` and returns its new handle.
+    ///
+    /// The source and destination shards are write-locked together, in the global lock order (by
+    /// map, then by shard index) shared by every multi-shard operation, so the value is never
+    /// unreachable. If `map` picks the shard that already holds the value, the handle is returned
+    /// as is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value was evicted from the map.
+    ///
+    /// Time complexity: O(1)
+    pub fn move_to(mut self, map: &SlotMap<T>) -> SlotMapId<T> {
+        let shard = unsafe { map.shards.get_unchecked(map.select_shard(&[])) };
+
+        if Arc::ptr_eq(shard, &self.from) {
+            assert!(!self.is_evicted(), "{}", EVICTED);
+            return self;
+        }
+
+        let (mut source, mut target) = if self.from.lock_order() < shard.lock_order() {
+            let source = self.lock_write();
+            (source, shard.inner.write())
+        } else {
+            let target = shard.inner.write();
+            (self.lock_write(), target)
+        };
+        assert!(source.contains(self.id, self.generation), "{}", EVICTED);
+
+        let value = unsafe { source.remove_unchecked(self.id) };
+        self.from.len.fetch_sub(1, Ordering::Relaxed);
+        let id = target.insert(value);
+        let generation = unsafe { target.get_unchecked_generation(id) };
+        shard.len.fetch_add(1, Ordering::Relaxed);
+        drop((source, target));
+        unsafe { ManuallyDrop::drop(&mut self.from) };
+        mem::forget(self);
+
+        SlotMapId {
+            from: ManuallyDrop::new(shard.clone()),
+            id,
+            generation,
+        }
+    }
+
+    /// Returns whether the value was evicted by [`SlotMap::retain`], [`SlotMap::remove_where`]
+    /// or [`SlotMap::clear`].
+    ///
+    /// Time complexity: O(1)
+    pub fn is_evicted(&self) -> bool {
+        !self.lock_read().contains(self.id, self.generation)
+    }
+
     /// Returns an immutable reference to the value, holding a read lock until the ref is dropped.
     ///
+    /// # Panics
+    ///
+    /// Panics if the value was evicted from the map.
+    ///
     /// Time complexity: O(1)
     pub fn get(&self) -> SlotMapRef<'_, T> {
-        let guard = self.from.inner.read();
+        let guard = self.lock_read();
+        assert!(guard.contains(self.id, self.generation), "{}", EVICTED);
         SlotMapRef { guard, id: self.id }
     }
 
     /// Returns a mutable reference to the value, holding a write lock until the ref is dropped.
     ///
+    /// # Panics
+    ///
+    /// Panics if the value was evicted from the map.
+    ///
     /// Time complexity: O(1)
     pub fn get_mut(&self) -> SlotMapRefMut<'_, T> {
-        let guard = self.from.inner.write();
+        let guard = self.lock_write();
+        assert!(guard.contains(self.id, self.generation), "{}", EVICTED);
         SlotMapRefMut { guard, id: self.id }
     }
+
+    /// Like [`get`](Self::get), but fails with [`SlotMapTryGetError::Locked`] instead of blocking
+    /// if the lock is held, or with [`SlotMapTryGetError::Evicted`] if the value was evicted.
+    ///
+    /// Time complexity: O(1)
+    pub fn try_get(&self) -> Result<SlotMapRef<'_, T>, SlotMapTryGetError> {
+        let guard = self.checked(self.from.inner.try_read())?;
+        Ok(SlotMapRef { guard, id: self.id })
+    }
+
+    /// Like [`try_get`](Self::try_get), but waits up to `timeout` for the lock.
+    pub fn get_for(&self, timeout: Duration) -> Result<SlotMapRef<'_, T>, SlotMapTryGetError> {
+        let guard = self.checked(self.from.inner.try_read_for(timeout))?;
+        Ok(SlotMapRef { guard, id: self.id })
+    }
+
+    /// Like [`try_get`](Self::try_get), but waits until `deadline` for the lock.
+    pub fn get_until(&self, deadline: Instant) -> Result<SlotMapRef<'_, T>, SlotMapTryGetError> {
+        let guard = self.checked(self.from.inner.try_read_until(deadline))?;
+        Ok(SlotMapRef { guard, id: self.id })
+    }
+
+    /// Like [`get_mut`](Self::get_mut), but fails with [`SlotMapTryGetError::Locked`] instead of
+    /// blocking if the lock is held, or with [`SlotMapTryGetError::Evicted`] if the value was
+    /// evicted.
+    ///
+    /// Time complexity: O(1)
+    pub fn try_get_mut(&self) -> Result<SlotMapRefMut<'_, T>, SlotMapTryGetError> {
+        let guard = self.checked(self.from.inner.try_write())?;
+        Ok(SlotMapRefMut { guard, id: self.id })
+    }
+
+    /// Like [`try_get_mut`](Self::try_get_mut), but waits up to `timeout` for the lock.
+    pub fn get_mut_for(
+        &self,
+        timeout: Duration,
+    ) -> Result<SlotMapRefMut<'_, T>, SlotMapTryGetError> {
+        let guard = self.checked(self.from.inner.try_write_for(timeout))?;
+        Ok(SlotMapRefMut { guard, id: self.id })
+    }
+
+    /// Like [`try_get_mut`](Self::try_get_mut), but waits until `deadline` for the lock.
+    pub fn get_mut_until(
+        &self,
+        deadline: Instant,
+    ) -> Result<SlotMapRefMut<'_, T>, SlotMapTryGetError> {
+        let guard = self.checked(self.from.inner.try_write_until(deadline))?;
+        Ok(SlotMapRefMut { guard, id: self.id })
+    }
+
+    /// Returns an immutable reference to the value, holding an upgradable read lock until the ref
+    /// is dropped.
+    ///
+    /// Only one upgradable lock can be held per shard at a time, but it coexists with plain
+    /// readers. Use [`SlotMapRefUpgradable::upgrade`] to mutate without letting another writer in
+    /// between.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value was evicted from the map.
+    ///
+    /// Time complexity: O(1)
+    pub fn get_upgradable(&self) -> SlotMapRefUpgradable<'_, T> {
+        let guard = self.lock_upgradable();
+        assert!(guard.contains(self.id, self.generation), "{}", EVICTED);
+        SlotMapRefUpgradable { guard, id: self.id }
+    }
+
+    /// Creates a non-owning handle to the value.
+    ///
+    /// Time complexity: O(1)
+    pub fn downgrade(&self) -> SlotMapWeakId<T> {
+        SlotMapWeakId {
+            from: Arc::clone(&self.from),
+            id: self.id,
+            generation: self.generation,
+        }
+    }
+
+    /// Converts the handle into a reference-counted handle without moving the value.
+    ///
+    /// Time complexity: O(1)
+    pub fn into_shared(mut self) -> SlotMapSharedId<T> {
+        let guard = self.lock_read();
+
+        if guard.contains(self.id, self.generation) {
+            unsafe { guard.get_unchecked_refs(self.id) }.store(1, Ordering::Relaxed);
+        }
+
+        drop(guard);
+        let from = ManuallyDrop::new(unsafe { ManuallyDrop::take(&mut self.from) });
+        let (id, generation) = (self.id, self.generation);
+        mem::forget(self);
+        SlotMapSharedId {
+            from,
+            id,
+            generation,
+        }
+    }
+
+    fn checked<G>(&self, guard: Option<G>) -> Result<G, SlotMapTryGetError>
+    where
+        G: Deref<Target = inner::SlotMap<T>>,
+    {
+        let guard = guard.ok_or(SlotMapTryGetError::Locked)?;
+
+        if guard.contains(self.id, self.generation) {
+            Ok(guard)
+        } else {
+            Err(SlotMapTryGetError::Evicted)
+        }
+    }
+}
+
+impl<T> SlotMapId<T> {
+    fn identity(&self) -> (usize, usize, usize, u32) {
+        (self.from.map, self.from.index, self.id, self.generation)
+    }
+
+    fn lock_read(&self) -> RwLockReadGuard<'_, inner::SlotMap<T>> {
+        util::requesting(self, || self.from.inner.read())
+    }
+    fn lock_write(&self) -> RwLockWriteGuard<'_, inner::SlotMap<T>> {
+        util::requesting(self, || self.from.inner.write())
+    }
+    fn lock_upgradable(&self) -> RwLockUpgradableReadGuard<'_, inner::SlotMap<T>> {
+        util::requesting(self, || self.from.inner.upgradable_read())
+    }
 }
 
 impl<T> fmt::Debug for SlotMapId<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SlotMapId")
-            .field("from", &self.from.as_ptr())
+            .field("shard", &self.from.index)
             .field("id", &self.id)
+            .field("generation", &self.generation)
             .finish()
     }
 }
 
+impl<T> PartialEq for SlotMapId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl<T> Eq for SlotMapId<T> {}
+
+impl<T> PartialOrd for SlotMapId<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for SlotMapId<T> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.identity().cmp(&other.identity())
+    }
+}
+
+impl<T> Hash for SlotMapId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state)
+    }
+}
+
 impl<T> Drop for SlotMapId<T> {
     fn drop(&mut self) {
-        let mut guard = self.from.inner.write();
-        unsafe { guard.remove_unchecked(self.id) };
+        let mut guard = self.lock_write();
+
+        if guard.contains(self.id, self.generation) {
+            unsafe { guard.remove_unchecked(self.id) };
+            self.from.len.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        drop(guard);
+        unsafe { ManuallyDrop::drop(&mut self.from) }
+    }
+}
+
+/// Reference-counted handle to a value in a [`SlotMap`].
+///
+/// Cloning it shares ownership of the value; the count is stored in the map entry itself.
+/// Dropping the last clone removes the value from the map.
+///
+/// Handles compare, hash and order like [`SlotMapId`], so clones are equal.
+pub struct SlotMapSharedId<T> {
+    from: ManuallyDrop<Arc<Shard<T>>>,
+    id: usize,
+    generation: u32,
+}
+
+impl<T> SlotMapSharedId<T> {
+    /// Returns the number of handles sharing the value, or `0` if the value was evicted.
+    ///
+    /// Time complexity: O(1)
+    pub fn strong_count(&self) -> usize {
+        let guard = self.lock_read();
+
+        if !guard.contains(self.id, self.generation) {
+            return 0;
+        }
+
+        unsafe { guard.get_unchecked_refs(self.id) }.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether the value was evicted by [`SlotMap::retain`], [`SlotMap::remove_where`]
+    /// or [`SlotMap::clear`].
+    ///
+    /// Time complexity: O(1)
+    pub fn is_evicted(&self) -> bool {
+        !self.lock_read().contains(self.id, self.generation)
+    }
+
+    /// Takes the value out of the map if this is the only handle, otherwise returns the handle.
+    ///
+    /// Also returns the handle if the value was evicted.
+    ///
+    /// Time complexity: O(1)
+    pub fn try_unwrap(mut self) -> Result<T, Self> {
+        let mut guard = self.lock_write();
+
+        if !guard.contains(self.id, self.generation)
+            || unsafe { guard.get_unchecked_refs(self.id) }.load(Ordering::Relaxed) != 1
+        {
+            drop(guard);
+            return Err(self);
+        }
+
+        let value = unsafe { guard.remove_unchecked(self.id) };
         self.from.len.fetch_sub(1, Ordering::Relaxed);
+        drop(guard);
+        unsafe { ManuallyDrop::drop(&mut self.from) };
+        mem::forget(self);
+        Ok(value)
+    }
+
+    /// Returns an immutable reference to the value, holding a read lock until the ref is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value was evicted from the map.
+    ///
+    /// Time complexity: O(1)
+    pub fn get(&self) -> SlotMapRef<'_, T> {
+        let guard = self.lock_read();
+        assert!(guard.contains(self.id, self.generation), "{}", EVICTED);
+        SlotMapRef { guard, id: self.id }
+    }
+
+    /// Returns a mutable reference to the value, holding a write lock until the ref is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value was evicted from the map.
+    ///
+    /// Time complexity: O(1)
+    pub fn get_mut(&self) -> SlotMapRefMut<'_, T> {
+        let guard = self.lock_write();
+        assert!(guard.contains(self.id, self.generation), "{}", EVICTED);
+        SlotMapRefMut { guard, id: self.id }
+    }
+
+    /// Creates a non-owning handle to the value.
+    ///
+    /// Time complexity: O(1)
+    pub fn downgrade(&self) -> SlotMapWeakId<T> {
+        SlotMapWeakId {
+            from: Arc::clone(&self.from),
+            id: self.id,
+            generation: self.generation,
+        }
+    }
+}
+
+impl<T> Clone for SlotMapSharedId<T> {
+    fn clone(&self) -> Self {
+        let guard = self.lock_read();
+
+        if guard.contains(self.id, self.generation) {
+            unsafe { guard.get_unchecked_refs(self.id) }.fetch_add(1, Ordering::Relaxed);
+        }
+
+        drop(guard);
+        Self {
+            from: self.from.clone(),
+            id: self.id,
+            generation: self.generation,
+        }
+    }
+}
+
+impl<T> SlotMapSharedId<T> {
+    fn identity(&self) -> (usize, usize, usize, u32) {
+        (self.from.map, self.from.index, self.id, self.generation)
+    }
+
+    fn lock_read(&self) -> RwLockReadGuard<'_, inner::SlotMap<T>> {
+        util::requesting(self, || self.from.inner.read())
+    }
+    fn lock_write(&self) -> RwLockWriteGuard<'_, inner::SlotMap<T>> {
+        util::requesting(self, || self.from.inner.write())
+    }
+}
+
+impl<T> fmt::Debug for SlotMapSharedId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotMapSharedId")
+            .field("shard", &self.from.index)
+            .field("id", &self.id)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+impl<T> PartialEq for SlotMapSharedId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl<T> Eq for SlotMapSharedId<T> {}
+
+impl<T> PartialOrd for SlotMapSharedId<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for SlotMapSharedId<T> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.identity().cmp(&other.identity())
+    }
+}
+
+impl<T> Hash for SlotMapSharedId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state)
+    }
+}
+
+impl<T> Drop for SlotMapSharedId<T> {
+    fn drop(&mut self) {
+        let mut guard = self.lock_write();
+
+        if guard.contains(self.id, self.generation)
+            && unsafe { guard.get_unchecked_refs(self.id) }.fetch_sub(1, Ordering::Relaxed) == 1
+        {
+            unsafe { guard.remove_unchecked(self.id) };
+            self.from.len.fetch_sub(1, Ordering::Relaxed);
+        }
+
         drop(guard);
         unsafe { ManuallyDrop::drop(&mut self.from) }
     }
 }
 
+/// Non-owning handle to a value in a [`SlotMap`], created by [`SlotMapId::downgrade`] or
+/// [`SlotMapSharedId::downgrade`].
+///
+/// It does not keep the value alive: once the owning [`SlotMapId`] is dropped, every accessor
+/// returns `None`, even if the slot is later reused by another value.
+///
+/// Like [`std::sync::Weak`], it is only [`Send`] when `T` is also [`Sync`], since clones on
+/// several threads may read the value at once:
+///
+/// ```compile_fail
+/// use deadlock::SlotMap;
+/// use std::{cell::Cell, thread};
+///
+/// let map = SlotMap::new();
+/// let id = map.insert(Cell::new(0));
+/// let weak = id.downgrade();
+/// thread::spawn(move || weak.get().unwrap().set(1));
+/// ```
+pub struct SlotMapWeakId<T> {
+    from: Arc<Shard<T>>,
+    id: usize,
+    generation: u32,
+}
+
+impl<T> SlotMapWeakId<T> {
+    /// Returns a new shared handle if the value is still in the map and owned through
+    /// [`SlotMapSharedId`]s.
+    ///
+    /// Always returns `None` for values owned by a unique [`SlotMapId`].
+    ///
+    /// Time complexity: O(1)
+    pub fn upgrade(&self) -> Option<SlotMapSharedId<T>> {
+        let guard = self.lock_read();
+
+        if !guard.contains(self.id, self.generation) {
+            return None;
+        }
+
+        let refs = unsafe { guard.get_unchecked_refs(self.id) };
+
+        if matches!(refs.load(Ordering::Relaxed), 0 | raw::RAW_OWNED) {
+            return None;
+        }
+
+        refs.fetch_add(1, Ordering::Relaxed);
+        drop(guard);
+        Some(SlotMapSharedId {
+            from: ManuallyDrop::new(self.from.clone()),
+            id: self.id,
+            generation: self.generation,
+        })
+    }
+
+    /// Returns whether the value is still in the map.
+    ///
+    /// Time complexity: O(1)
+    pub fn is_alive(&self) -> bool {
+        self.lock_read().contains(self.id, self.generation)
+    }
+
+    /// Returns an immutable reference to the value, holding a read lock until the ref is dropped,
+    /// or `None` if the value was removed.
+    ///
+    /// Time complexity: O(1)
+    pub fn get(&self) -> Option<SlotMapRef<'_, T>> {
+        let guard = self.lock_read();
+        guard
+            .contains(self.id, self.generation)
+            .then(|| SlotMapRef { guard, id: self.id })
+    }
+
+    /// Returns a mutable reference to the value, holding a write lock until the ref is dropped,
+    /// or `None` if the value was removed.
+    ///
+    /// Time complexity: O(1)
+    pub fn get_mut(&self) -> Option<SlotMapRefMut<'_, T>> {
+        let guard = self.lock_write();
+        guard
+            .contains(self.id, self.generation)
+            .then(|| SlotMapRefMut { guard, id: self.id })
+    }
+}
+
+impl<T> Clone for SlotMapWeakId<T> {
+    fn clone(&self) -> Self {
+        Self {
+            from: Arc::clone(&self.from),
+            id: self.id,
+            generation: self.generation,
+        }
+    }
+}
+
+impl<T> SlotMapWeakId<T> {
+    fn lock_read(&self) -> RwLockReadGuard<'_, inner::SlotMap<T>> {
+        util::requesting(self, || self.from.inner.read())
+    }
+    fn lock_write(&self) -> RwLockWriteGuard<'_, inner::SlotMap<T>> {
+        util::requesting(self, || self.from.inner.write())
+    }
+}
+
+impl<T> fmt::Debug for SlotMapWeakId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotMapWeakId")
+            .field("shard", &self.from.index)
+            .field("id", &self.id)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
 /// Immutable reference to a value in a [`SlotMap`], holding a read lock.
 pub struct SlotMapRef<'a, T> {
     guard: RwLockReadGuard<'a, inner::SlotMap<T>>,
@@ -210,6 +1036,31 @@ impl<T> SlotMapRef<'_, T> {
     }
 }
 
+/// Immutable reference to a value in a [`SlotMap`], holding an upgradable read lock.
+pub struct SlotMapRefUpgradable<'a, T> {
+    guard: RwLockUpgradableReadGuard<'a, inner::SlotMap<T>>,
+    id: usize,
+}
+
+#[reflica::reflica]
+impl<T> SlotMapRefUpgradable<'_, T> {
+    fn deref(&self) -> &T {
+        unsafe { self.guard.get_unchecked(self.id) }
+    }
+}
+
+impl<'a, T> SlotMapRefUpgradable<'a, T> {
+    /// Atomically upgrades to a write lock, waiting for other readers to leave.
+    ///
+    /// No other writer can modify the value between this ref and the returned one.
+    pub fn upgrade(self) -> SlotMapRefMut<'a, T> {
+        SlotMapRefMut {
+            guard: RwLockUpgradableReadGuard::upgrade(self.guard),
+            id: self.id,
+        }
+    }
+}
+
 /// Mutable reference to a value in a [`SlotMap`], holding a write lock.
 pub struct SlotMapRefMut<'a, T> {
     guard: RwLockWriteGuard<'a, inner::SlotMap<T>>,
@@ -227,85 +1078,299 @@ impl<T> SlotMapRefMut<'_, T> {
     }
 }
 
+impl<'a, T> SlotMapRefMut<'a, T> {
+    /// Atomically downgrades to a read lock.
+    ///
+    /// No other writer can modify the value between this ref and the returned one.
+    pub fn downgrade(self) -> SlotMapRef<'a, T> {
+        SlotMapRef {
+            guard: RwLockWriteGuard::downgrade(self.guard),
+            id: self.id,
+        }
+    }
+}
+
 /// A read-locked view of a single internal shard of a [`SlotMap`].
 ///
 /// Created by [`SlotMap::shards`]. Holds a read lock on the shard for its entire lifetime,
 /// preventing concurrent writes to that shard while the reference exists.
 pub struct SlotMapShardRef<'a, T> {
     guard: RwLockReadGuard<'a, inner::SlotMap<T>>,
+    shard: usize,
 }
 
 impl<T> SlotMapShardRef<'_, T> {
-    /// Returns an iterator over immutable references to all values in this shard.
+    /// Returns the number of values in this shard.
+    pub fn len(&self) -> usize {
+        self.guard.len()
+    }
+
+    /// Returns whether this shard is empty.
+    pub fn is_empty(&self) -> bool {
+        self.guard.len() == 0
+    }
+
+    /// Returns the number of values this shard can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.guard.capacity()
+    }
+
+    /// Returns an iterator over the keys of and immutable references to all values in this
+    /// shard.
     ///
     /// The read lock is held for the entire lifetime of the returned iterator.
-    pub fn iter(&self) -> impl Iterator<Item = &T> {
-        (0..self.guard.len()).map(move |index| unsafe { self.guard.get_unchecked_nth(index) })
+    pub fn iter(&self) -> impl Iterator<Item = (SlotMapKey, &T)> {
+        (0..self.guard.len()).map(move |index| {
+            let id = unsafe { self.guard.get_unchecked_nth_id(index) };
+            let generation = unsafe { self.guard.get_unchecked_generation(id) };
+            (SlotMapKey::new(self.shard, id, generation), unsafe {
+                self.guard.get_unchecked(id)
+            })
+        })
+    }
+}
+
+/// A write-locked view of a single internal shard of a [`SlotMap`].
+///
+/// Created by [`SlotMap::shards_mut`]. Holds a write lock on the shard for its entire lifetime,
+/// so bulk updates pay for one lock acquisition per shard instead of one per value.
+pub struct SlotMapShardRefMut<'a, T> {
+    guard: RwLockWriteGuard<'a, inner::SlotMap<T>>,
+    shard: usize,
+}
+
+impl<T> SlotMapShardRefMut<'_, T> {
+    /// Returns the number of values in this shard.
+    pub fn len(&self) -> usize {
+        self.guard.len()
+    }
+
+    /// Returns whether this shard is empty.
+    pub fn is_empty(&self) -> bool {
+        self.guard.len() == 0
+    }
+
+    /// Returns the number of values this shard can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.guard.capacity()
+    }
+
+    /// Returns an iterator over the keys of and immutable references to all values in this
+    /// shard.
+    pub fn iter(&self) -> impl Iterator<Item = (SlotMapKey, &T)> {
+        (0..self.guard.len()).map(move |index| {
+            let id = unsafe { self.guard.get_unchecked_nth_id(index) };
+            let generation = unsafe { self.guard.get_unchecked_generation(id) };
+            (SlotMapKey::new(self.shard, id, generation), unsafe {
+                self.guard.get_unchecked(id)
+            })
+        })
+    }
+
+    /// Returns an iterator over the keys of and mutable references to all values in this shard.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (SlotMapKey, &mut T)> {
+        let shard = self.shard;
+        self.guard
+            .iter_mut()
+            .map(move |(id, generation, value)| (SlotMapKey::new(shard, id, generation), value))
+    }
+
+    /// Calls `f` on the key of and a mutable reference to every value in this shard.
+    pub fn for_each_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(SlotMapKey, &mut T),
+    {
+        self.iter_mut().for_each(|(key, value)| f(key, value))
     }
 }
 
-/// Iterator over immutable references to values in a [`SlotMap`].
+/// Iterator over the keys of and immutable references to values in a [`SlotMap`].
 ///
 /// Created by [`SlotMap::iter`]. Each call to [`next`](Iterator::next) acquires and releases
 /// a read lock for a single element. This allows fine-grained locking but may have overhead
 /// when iterating many elements.
+///
+/// Values are visited by slot, so every value present for the whole iteration is yielded exactly
+/// once even if other values are inserted or removed concurrently. Values inserted during the
+/// iteration may or may not be yielded.
 pub struct SlotMapIter<'a, T> {
     shards: &'a [Arc<Shard<T>>],
+    capacities: Box<[usize]>,
     shard_index: usize,
-    inner_index: usize,
+    slot: usize,
 }
 
 impl<'a, T> Iterator for SlotMapIter<'a, T> {
-    type Item = SlotMapRef<'a, T>;
+    type Item = (SlotMapKey, SlotMapRef<'a, T>);
 
     fn next(&mut self) -> Option<Self::Item> {
         Some(loop {
             let shard = self.shards.get(self.shard_index)?;
+            let capacity = unsafe { *self.capacities.get_unchecked(self.shard_index) };
             let guard = shard.inner.read();
 
-            if self.inner_index >= guard.len() {
-                self.shard_index += 1;
-                self.inner_index = 0;
-                continue;
+            if let Some(id) = (self.slot..capacity).find(|&id| guard.is_occupied(id)) {
+                let generation = unsafe { guard.get_unchecked_generation(id) };
+                self.slot = id + 1;
+                break (
+                    SlotMapKey::new(shard.index, id, generation),
+                    SlotMapRef { guard, id },
+                );
             }
 
-            let id = unsafe { guard.get_unchecked_nth_id(self.inner_index) };
-            self.inner_index += 1;
-            break SlotMapRef { guard, id };
+            self.shard_index += 1;
+            self.slot = 0;
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let slots = self.capacities.iter().skip(self.shard_index).sum::<usize>();
+        (0, Some(slots - self.slot))
+    }
 }
 
-/// Iterator over mutable references to values in a [`SlotMap`].
+/// Iterator over the keys of and mutable references to values in a [`SlotMap`].
 ///
 /// Created by [`SlotMap::iter_mut`]. Each call to [`next`](Iterator::next) acquires and releases
 /// a write lock for a single element. This allows fine-grained locking but may have overhead
 /// when iterating many elements.
+///
+/// Concurrent insertions and removals are handled as described in [`SlotMapIter`].
 pub struct SlotMapIterMut<'a, T> {
     shards: &'a [Arc<Shard<T>>],
+    capacities: Box<[usize]>,
     shard_index: usize,
-    inner_index: usize,
+    slot: usize,
 }
 
 impl<'a, T> Iterator for SlotMapIterMut<'a, T> {
-    type Item = SlotMapRefMut<'a, T>;
+    type Item = (SlotMapKey, SlotMapRefMut<'a, T>);
 
     fn next(&mut self) -> Option<Self::Item> {
         Some(loop {
             let shard = self.shards.get(self.shard_index)?;
+            let capacity = unsafe { *self.capacities.get_unchecked(self.shard_index) };
             let guard = shard.inner.write();
 
-            if self.inner_index >= guard.len() {
-                self.shard_index += 1;
-                self.inner_index = 0;
-                continue;
+            if let Some(id) = (self.slot..capacity).find(|&id| guard.is_occupied(id)) {
+                let generation = unsafe { guard.get_unchecked_generation(id) };
+                self.slot = id + 1;
+                break (
+                    SlotMapKey::new(shard.index, id, generation),
+                    SlotMapRefMut { guard, id },
+                );
             }
 
-            let id = unsafe { guard.get_unchecked_nth_id(self.inner_index) };
-            self.inner_index += 1;
-            break SlotMapRefMut { guard, id };
+            self.shard_index += 1;
+            self.slot = 0;
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let slots = self.capacities.iter().skip(self.shard_index).sum::<usize>();
+        (0, Some(slots - self.slot))
+    }
+}
+
+/// Error returned by [`SlotMapId::try_get`], [`SlotMapId::try_get_mut`] and their timed
+/// variants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotMapTryGetError {
+    /// The lock was held, or the timeout elapsed before it was released. Retrying may succeed.
+    Locked,
+    /// The value was evicted from the map. Retrying will never succeed.
+    Evicted,
+}
+
+impl fmt::Display for SlotMapTryGetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Locked => f.write_str("slot map shard is locked"),
+            Self::Evicted => f.write_str(EVICTED),
+        }
+    }
+}
+
+impl error::Error for SlotMapTryGetError {}
+
+impl<T> fmt::Debug for SlotMap<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotMap")
+            .field("shards", &self.shards)
+            .finish()
+    }
+}
+
+impl<T> fmt::Debug for Shard<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Shard");
+        d.field("index", &self.index)
+            .field("len", &self.len.load(Ordering::Relaxed));
+
+        match self.inner.try_read() {
+            Some(guard) => d.field(
+                "values",
+                &SlotMapShardRef {
+                    guard,
+                    shard: self.index,
+                },
+            ),
+            None => d.field("values", &format_args!("<locked>")),
+        };
+
+        d.finish()
+    }
+}
+
+impl<T> fmt::Debug for SlotMapRef<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> fmt::Debug for SlotMapRefUpgradable<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> fmt::Debug for SlotMapRefMut<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> fmt::Debug for SlotMapShardRef<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> fmt::Debug for SlotMapShardRefMut<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
 }
 
 unsafe impl<T> Send for SlotMap<T> where T: Send {}
@@ -314,15 +1379,27 @@ unsafe impl<T> Sync for SlotMap<T> where T: Send + Sync {}
 unsafe impl<T> Send for SlotMapId<T> where T: Send {}
 unsafe impl<T> Sync for SlotMapId<T> where T: Send + Sync {}
 
+unsafe impl<T> Send for SlotMapSharedId<T> where T: Send + Sync {}
+unsafe impl<T> Sync for SlotMapSharedId<T> where T: Send + Sync {}
+
+unsafe impl<T> Send for SlotMapWeakId<T> where T: Send + Sync {}
+unsafe impl<T> Sync for SlotMapWeakId<T> where T: Send + Sync {}
+
 unsafe impl<T> Send for SlotMapRef<'_, T> where T: Send + Sync {}
 unsafe impl<T> Sync for SlotMapRef<'_, T> where T: Send + Sync {}
 
+unsafe impl<T> Send for SlotMapRefUpgradable<'_, T> where T: Send + Sync {}
+unsafe impl<T> Sync for SlotMapRefUpgradable<'_, T> where T: Send + Sync {}
+
 unsafe impl<T> Send for SlotMapRefMut<'_, T> where T: Send {}
 unsafe impl<T> Sync for SlotMapRefMut<'_, T> where T: Send + Sync {}
 
 unsafe impl<T> Send for SlotMapShardRef<'_, T> where T: Send + Sync {}
 unsafe impl<T> Sync for SlotMapShardRef<'_, T> where T: Send + Sync {}
 
+unsafe impl<T> Send for SlotMapShardRefMut<'_, T> where T: Send {}
+unsafe impl<T> Sync for SlotMapShardRefMut<'_, T> where T: Send + Sync {}
+
 unsafe impl<T> Send for SlotMapIter<'_, T> where T: Send + Sync {}
 unsafe impl<T> Sync for SlotMapIter<'_, T> where T: Send + Sync {}
 
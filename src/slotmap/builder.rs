@@ -0,0 +1,120 @@
+use std::{error, fmt};
+
+use super::raw::MAX_SHARDS;
+use crate::{inner, util, RoundRobin, ShardSelector, SlotMap};
+
+/// Builder for a [`SlotMap`] with explicit shard count, per-shard capacity and shard selector.
+///
+/// ```
+/// use deadlock::{LeastLoaded, SlotMapBuilder};
+///
+/// let map = SlotMapBuilder::new()
+///     .num_shards(3)
+///     .capacity(16)
+///     .selector(LeastLoaded)
+///     .build()
+///     .unwrap();
+/// let id = map.insert(42);
+/// assert_eq!(*id.get(), 42);
+/// ```
+pub struct SlotMapBuilder {
+    num_shards: Option<usize>,
+    capacity: usize,
+    selector: Option<Box<dyn ShardSelector>>,
+}
+
+impl SlotMapBuilder {
+    /// Creates a builder with the default shard count, no pre-allocation and
+    /// [`RoundRobin::default`].
+    pub fn new() -> Self {
+        Self {
+            num_shards: None,
+            capacity: 0,
+            selector: None,
+        }
+    }
+
+    /// Sets the number of shards. Any count in `1..=4096` is allowed, including non-powers of two.
+    pub fn num_shards(mut self, num_shards: usize) -> Self {
+        self.num_shards = Some(num_shards);
+        self
+    }
+
+    /// Sets the number of entries pre-allocated in each shard.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets how [`SlotMap::insert`] picks a shard.
+    pub fn selector<S>(mut self, selector: S) -> Self
+    where
+        S: ShardSelector + 'static,
+    {
+        self.selector = Some(Box::new(selector));
+        self
+    }
+
+    /// Validates the configuration and creates the slot map.
+    pub fn build<T>(self) -> Result<SlotMap<T>, SlotMapBuilderError> {
+        let num_shards = self
+            .num_shards
+            .unwrap_or_else(|| util::default_num_shards().min(MAX_SHARDS));
+
+        if num_shards == 0 {
+            return Err(SlotMapBuilderError::ZeroShards);
+        }
+
+        if num_shards > MAX_SHARDS {
+            return Err(SlotMapBuilderError::TooManyShards);
+        }
+
+        let inners = (0..num_shards)
+            .map(|_| inner::SlotMap::try_with_capacity(self.capacity))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(SlotMapBuilderError::CapacityOverflow)?;
+        let selector = self
+            .selector
+            .unwrap_or_else(|| Box::new(RoundRobin::default()));
+
+        Ok(unsafe { SlotMap::from_inners_unchecked(inners, selector) })
+    }
+}
+
+impl Default for SlotMapBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for SlotMapBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotMapBuilder")
+            .field("num_shards", &self.num_shards)
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Error returned by [`SlotMapBuilder::build`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotMapBuilderError {
+    /// The shard count was zero.
+    ZeroShards,
+    /// The shard count exceeded 4096, the most a [`RawKey`](crate::RawKey) can address.
+    TooManyShards,
+    /// The per-shard capacity does not fit in memory.
+    CapacityOverflow,
+}
+
+impl fmt::Display for SlotMapBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroShards => f.write_str("slot map needs at least one shard"),
+            Self::TooManyShards => f.write_str("slot map supports at most 4096 shards"),
+            Self::CapacityOverflow => f.write_str("slot map capacity overflow"),
+        }
+    }
+}
+
+impl error::Error for SlotMapBuilderError {}
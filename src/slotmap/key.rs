@@ -0,0 +1,84 @@
+use crate::{SlotMap, SlotMapId, SlotMapRef, SlotMapRefMut, SlotMapSharedId, SlotMapWeakId};
+
+/// Lightweight, non-owning key to a value in a [`SlotMap`].
+///
+/// Unlike [`SlotMapWeakId`] it holds no reference to the map, so it is `Copy` and can be stored
+/// freely; it is resolved with [`SlotMap::get`] and [`SlotMap::get_mut`]. A key stays invalid once
+/// its value is removed, even if the slot is reused. Resolving a key against a map it was not
+/// created from gives an unspecified (but safe) result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlotMapKey {
+    pub(crate) shard: usize,
+    pub(crate) id: usize,
+    pub(crate) generation: u32,
+}
+
+impl SlotMapKey {
+    pub(crate) fn new(shard: usize, id: usize, generation: u32) -> Self {
+        Self {
+            shard,
+            id,
+            generation,
+        }
+    }
+}
+
+impl<T> SlotMap<T> {
+    /// Returns an immutable reference to the value of `key`, holding a read lock until the ref is
+    /// dropped, or `None` if the value was removed.
+    ///
+    /// Time complexity: O(1)
+    pub fn get(&self, key: SlotMapKey) -> Option<SlotMapRef<'_, T>> {
+        let shard = self.shards.get(key.shard)?;
+        let guard = shard.inner.read();
+        guard
+            .contains(key.id, key.generation)
+            .then(|| SlotMapRef { guard, id: key.id })
+    }
+
+    /// Returns a mutable reference to the value of `key`, holding a write lock until the ref is
+    /// dropped, or `None` if the value was removed.
+    ///
+    /// Time complexity: O(1)
+    pub fn get_mut(&self, key: SlotMapKey) -> Option<SlotMapRefMut<'_, T>> {
+        let shard = self.shards.get(key.shard)?;
+        let guard = shard.inner.write();
+        guard
+            .contains(key.id, key.generation)
+            .then(|| SlotMapRefMut { guard, id: key.id })
+    }
+}
+
+impl<T> SlotMapId<T> {
+    /// Returns the key of the value.
+    pub fn key(&self) -> SlotMapKey {
+        SlotMapKey {
+            shard: self.from.index,
+            id: self.id,
+            generation: self.generation,
+        }
+    }
+}
+
+impl<T> SlotMapSharedId<T> {
+    /// Returns the key of the value.
+    pub fn key(&self) -> SlotMapKey {
+        SlotMapKey {
+            shard: self.from.index,
+            id: self.id,
+            generation: self.generation,
+        }
+    }
+}
+
+impl<T> SlotMapWeakId<T> {
+    /// Returns the key of the value.
+    pub fn key(&self) -> SlotMapKey {
+        SlotMapKey {
+            shard: self.from.index,
+            id: self.id,
+            generation: self.generation,
+        }
+    }
+}
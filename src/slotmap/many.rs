@@ -0,0 +1,126 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+    ptr::NonNull,
+};
+use triomphe::Arc;
+
+use super::EVICTED;
+use crate::{inner, util::RwLockWriteGuard, SlotMap, SlotMapId};
+
+impl<T> SlotMap<T> {
+    /// Returns mutable references to the values of several handles at once, holding the write
+    /// lock of every shard involved until the returned ref is dropped.
+    ///
+    /// Handles in the same shard share one lock, and distinct shards are locked in the global
+    /// lock order (by map, then by shard index) shared by every multi-shard operation, so this
+    /// neither self-deadlocks nor deadlocks against other threads doing the same. The handles
+    /// stay borrowed until the returned ref is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a handle does not belong to this map, if the same handle is passed twice, or if
+    /// a value was evicted from the map.
+    ///
+    /// Time complexity: O(N²)
+    pub fn get_many_mut<'a, const N: usize>(
+        &'a self,
+        ids: [&'a SlotMapId<T>; N],
+    ) -> SlotMapManyRefMut<'a, T, N> {
+        for (index, id) in ids.iter().enumerate() {
+            assert!(
+                self.shards
+                    .get(id.from.index)
+                    .is_some_and(|shard| Arc::ptr_eq(shard, &id.from)),
+                "handle does not belong to this map"
+            );
+            assert!(
+                ids[..index].iter().all(|other| other != id),
+                "handle was passed more than once"
+            );
+        }
+
+        let mut shards = ids
+            .iter()
+            .map(|id| unsafe { self.shards.get_unchecked(id.from.index) })
+            .collect::<Vec<_>>();
+        shards.sort_unstable_by_key(|shard| shard.lock_order());
+        shards.dedup_by_key(|shard| shard.index);
+
+        let mut guards = shards
+            .iter()
+            .map(|shard| shard.inner.write())
+            .collect::<Vec<_>>();
+        let values = ids.map(|id| {
+            let position = shards
+                .iter()
+                .position(|shard| shard.index == id.from.index)
+                .unwrap();
+            let guard = &mut guards[position];
+            assert!(guard.contains(id.id, id.generation), "{}", EVICTED);
+            NonNull::from(unsafe { guard.get_unchecked_mut(id.id) })
+        });
+
+        SlotMapManyRefMut {
+            _guards: guards,
+            values,
+        }
+    }
+}
+
+/// Mutable references to the values of several handles in a [`SlotMap`], holding the write lock
+/// of every shard involved.
+///
+/// Created by [`SlotMap::get_many_mut`]. Indexing follows the order of the handles passed in.
+///
+/// The handles stay borrowed for as long as the ref lives, so none of them can be dropped while
+/// it points at their values:
+///
+/// ```compile_fail
+/// use deadlock::SlotMap;
+///
+/// let map = SlotMap::new();
+/// let (a, b) = (map.insert(1), map.insert(2));
+/// let mut many = map.get_many_mut([&a, &b]);
+/// drop(a);
+/// many[0] += 1;
+/// ```
+pub struct SlotMapManyRefMut<'a, T, const N: usize> {
+    _guards: Vec<RwLockWriteGuard<'a, inner::SlotMap<T>>>,
+    values: [NonNull<T>; N],
+}
+
+impl<T, const N: usize> SlotMapManyRefMut<'_, T, N> {
+    /// Returns mutable references to all the values at once.
+    pub fn each_mut(&mut self) -> [&mut T; N] {
+        self.values.map(|mut value| unsafe { value.as_mut() })
+    }
+}
+
+impl<T, const N: usize> Index<usize> for SlotMapManyRefMut<'_, T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        unsafe { self.values[index].as_ref() }
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for SlotMapManyRefMut<'_, T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        unsafe { self.values[index].as_mut() }
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for SlotMapManyRefMut<'_, T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.values.iter().map(|value| unsafe { value.as_ref() }))
+            .finish()
+    }
+}
+
+unsafe impl<T, const N: usize> Send for SlotMapManyRefMut<'_, T, N> where T: Send {}
+unsafe impl<T, const N: usize> Sync for SlotMapManyRefMut<'_, T, N> where T: Send + Sync {}
@@ -0,0 +1,309 @@
+use std::{fmt, marker::PhantomData, ptr::NonNull};
+
+use crate::{
+    inner,
+    util::{RwLockReadGuard, RwLockWriteGuard},
+    SlotMapRef, SlotMapRefMut,
+};
+
+/// Immutable reference to a part of a value in a [`SlotMap`](crate::SlotMap), holding a read
+/// lock.
+///
+/// Created by [`SlotMapRef::map`] and friends.
+pub struct MappedSlotMapRef<'a, T, U>
+where
+    U: ?Sized,
+{
+    _guard: RwLockReadGuard<'a, inner::SlotMap<T>>,
+    value: NonNull<U>,
+}
+
+#[reflica::reflica]
+impl<T, U> MappedSlotMapRef<'_, T, U>
+where
+    U: ?Sized,
+{
+    fn deref(&self) -> &U {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+/// Mutable reference to a part of a value in a [`SlotMap`](crate::SlotMap), holding a write
+/// lock.
+///
+/// Created by [`SlotMapRefMut::map`] and friends.
+///
+/// Like a `&mut U`, it is invariant in `U`, so a projection cannot be shortened to a type that
+/// would let a shorter-lived value be written into the map:
+///
+/// ```compile_fail
+/// use deadlock::MappedSlotMapRefMut;
+///
+/// fn shorten<'a, 'b, T>(
+///     r: MappedSlotMapRefMut<'a, T, &'static str>,
+/// ) -> MappedSlotMapRefMut<'a, T, &'b str> {
+///     r
+/// }
+/// ```
+pub struct MappedSlotMapRefMut<'a, T, U>
+where
+    U: ?Sized,
+{
+    _guard: RwLockWriteGuard<'a, inner::SlotMap<T>>,
+    value: NonNull<U>,
+    _marker: PhantomData<&'a mut U>,
+}
+
+#[reflica::reflica]
+impl<T, U> MappedSlotMapRefMut<'_, T, U>
+where
+    U: ?Sized,
+{
+    fn deref(&self) -> &U {
+        unsafe { self.value.as_ref() }
+    }
+
+    fn deref_mut(&mut self) -> &mut U {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'a, T> SlotMapRef<'a, T> {
+    /// Projects the ref to a part of the value, keeping the read lock.
+    ///
+    /// This is an associated function so that it does not shadow a method of `T`.
+    pub fn map<U, F>(r: Self, f: F) -> MappedSlotMapRef<'a, T, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> &U,
+    {
+        let value = NonNull::from(f(&r));
+        MappedSlotMapRef {
+            _guard: r.guard,
+            value,
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<U, E, F>(r: Self, f: F) -> Result<MappedSlotMapRef<'a, T, U>, (Self, E)>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> Result<&U, E>,
+    {
+        match f(&r).map(NonNull::from) {
+            Ok(value) => Ok(MappedSlotMapRef {
+                _guard: r.guard,
+                value,
+            }),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<U, F>(r: Self, f: F) -> Result<MappedSlotMapRef<'a, T, U>, Self>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        match f(&r).map(NonNull::from) {
+            Some(value) => Ok(MappedSlotMapRef {
+                _guard: r.guard,
+                value,
+            }),
+            None => Err(r),
+        }
+    }
+}
+
+impl<'a, T> SlotMapRefMut<'a, T> {
+    /// Projects the ref to a part of the value, keeping the write lock.
+    ///
+    /// This is an associated function so that it does not shadow a method of `T`.
+    pub fn map<U, F>(mut r: Self, f: F) -> MappedSlotMapRefMut<'a, T, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let value = NonNull::from(f(&mut r));
+        MappedSlotMapRefMut {
+            _guard: r.guard,
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<U, E, F>(mut r: Self, f: F) -> Result<MappedSlotMapRefMut<'a, T, U>, (Self, E)>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> Result<&mut U, E>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Ok(value) => Ok(MappedSlotMapRefMut {
+                _guard: r.guard,
+                value,
+                _marker: PhantomData,
+            }),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<U, F>(mut r: Self, f: F) -> Result<MappedSlotMapRefMut<'a, T, U>, Self>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Some(value) => Ok(MappedSlotMapRefMut {
+                _guard: r.guard,
+                value,
+                _marker: PhantomData,
+            }),
+            None => Err(r),
+        }
+    }
+}
+
+impl<'a, T, U> MappedSlotMapRef<'a, T, U>
+where
+    U: ?Sized,
+{
+    /// Projects the ref further, keeping the read lock.
+    pub fn map<V, F>(r: Self, f: F) -> MappedSlotMapRef<'a, T, V>
+    where
+        V: ?Sized,
+        F: FnOnce(&U) -> &V,
+    {
+        let value = NonNull::from(f(&r));
+        MappedSlotMapRef {
+            _guard: r._guard,
+            value,
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<V, E, F>(r: Self, f: F) -> Result<MappedSlotMapRef<'a, T, V>, (Self, E)>
+    where
+        V: ?Sized,
+        F: FnOnce(&U) -> Result<&V, E>,
+    {
+        match f(&r).map(NonNull::from) {
+            Ok(value) => Ok(MappedSlotMapRef {
+                _guard: r._guard,
+                value,
+            }),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<V, F>(r: Self, f: F) -> Result<MappedSlotMapRef<'a, T, V>, Self>
+    where
+        V: ?Sized,
+        F: FnOnce(&U) -> Option<&V>,
+    {
+        match f(&r).map(NonNull::from) {
+            Some(value) => Ok(MappedSlotMapRef {
+                _guard: r._guard,
+                value,
+            }),
+            None => Err(r),
+        }
+    }
+}
+
+impl<'a, T, U> MappedSlotMapRefMut<'a, T, U>
+where
+    U: ?Sized,
+{
+    /// Projects the ref further, keeping the write lock.
+    pub fn map<V, F>(mut r: Self, f: F) -> MappedSlotMapRefMut<'a, T, V>
+    where
+        V: ?Sized,
+        F: FnOnce(&mut U) -> &mut V,
+    {
+        let value = NonNull::from(f(&mut r));
+        MappedSlotMapRefMut {
+            _guard: r._guard,
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back along with the error if `f` fails.
+    pub fn try_map<V, E, F>(mut r: Self, f: F) -> Result<MappedSlotMapRefMut<'a, T, V>, (Self, E)>
+    where
+        V: ?Sized,
+        F: FnOnce(&mut U) -> Result<&mut V, E>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Ok(value) => Ok(MappedSlotMapRefMut {
+                _guard: r._guard,
+                value,
+                _marker: PhantomData,
+            }),
+            Err(error) => Err((r, error)),
+        }
+    }
+
+    /// Like [`map`](Self::map), but gives the ref back if `f` returns `None`.
+    pub fn filter_map<V, F>(mut r: Self, f: F) -> Result<MappedSlotMapRefMut<'a, T, V>, Self>
+    where
+        V: ?Sized,
+        F: FnOnce(&mut U) -> Option<&mut V>,
+    {
+        match f(&mut r).map(NonNull::from) {
+            Some(value) => Ok(MappedSlotMapRefMut {
+                _guard: r._guard,
+                value,
+                _marker: PhantomData,
+            }),
+            None => Err(r),
+        }
+    }
+}
+
+impl<T, U> fmt::Debug for MappedSlotMapRef<'_, T, U>
+where
+    U: ?Sized + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T, U> fmt::Debug for MappedSlotMapRefMut<'_, T, U>
+where
+    U: ?Sized + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+unsafe impl<T, U> Send for MappedSlotMapRef<'_, T, U>
+where
+    T: Send + Sync,
+    U: ?Sized + Sync,
+{
+}
+unsafe impl<T, U> Sync for MappedSlotMapRef<'_, T, U>
+where
+    T: Send + Sync,
+    U: ?Sized + Sync,
+{
+}
+
+unsafe impl<T, U> Send for MappedSlotMapRefMut<'_, T, U>
+where
+    T: Send,
+    U: ?Sized + Send,
+{
+}
+unsafe impl<T, U> Sync for MappedSlotMapRefMut<'_, T, U>
+where
+    T: Send + Sync,
+    U: ?Sized + Sync,
+{
+}
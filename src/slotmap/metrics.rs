@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use crate::SlotMap;
+
+impl<T> SlotMap<T> {
+    /// Returns a snapshot of the occupancy and lock counters of every shard, indexed by shard.
+    ///
+    /// Each shard is read-locked briefly in turn, so the snapshot is not consistent across
+    /// shards. The lock counters of a shard are read before its lock is taken, so taking a
+    /// snapshot shows up in the next one.
+    ///
+    /// Time complexity: O(# of shards)
+    pub fn stats(&self) -> Vec<SlotMapShardStats> {
+        self.shards
+            .iter()
+            .map(|shard| {
+                let lock = unsafe { shard.inner.raw() }.stats();
+                let guard = shard.inner.read();
+                SlotMapShardStats {
+                    len: guard.len(),
+                    capacity: guard.capacity(),
+                    free: guard.capacity() - guard.len(),
+                    inserts: guard.inserts(),
+                    removes: guard.removes(),
+                    lock,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Occupancy and lock counters of one shard of a [`SlotMap`].
+///
+/// Created by [`SlotMap::stats`]. Counters are cumulative since the shard was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlotMapShardStats {
+    /// Number of values in the shard.
+    pub len: usize,
+    /// Number of slots allocated by the shard.
+    pub capacity: usize,
+    /// Number of slots on the shard's free list.
+    pub free: usize,
+    /// Number of values ever inserted into the shard.
+    pub inserts: u64,
+    /// Number of values ever removed from the shard, including evictions.
+    pub removes: u64,
+    /// Counters of the shard's lock.
+    pub lock: LockStats,
+}
+
+/// Counters of a shard or heap lock.
+///
+/// A lock is contended when it could not be taken immediately and the caller had to wait,
+/// whether or not the wait eventually succeeded. Upgradable reads count as reads, upgrading one
+/// counts as a write, and downgrading a write counts as a read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockStats {
+    /// Number of times the lock was taken for reading.
+    pub reads: u64,
+    /// Number of times the lock was taken for writing.
+    pub writes: u64,
+    /// Number of read attempts that had to wait.
+    pub contended_reads: u64,
+    /// Number of write attempts that had to wait.
+    pub contended_writes: u64,
+    /// Longest continuous time the lock was held by one or more readers, up to the last of them
+    /// releasing or upgrading.
+    pub max_read_hold: Duration,
+    /// Longest time the lock was held for writing, up to a release or downgrade.
+    pub max_write_hold: Duration,
+}
@@ -0,0 +1,155 @@
+use rayon::prelude::*;
+use std::{fmt, ptr::NonNull};
+use triomphe::Arc;
+
+use crate::{
+    inner,
+    util::{self, RwLockReadGuard, RwLockWriteGuard},
+    SlotMap, SlotMapKey, SlotMapShardRef,
+};
+
+impl<T> SlotMap<T>
+where
+    T: Send + Sync,
+{
+    /// Creates a parallel iterator over the keys of and immutable references to values in the
+    /// map.
+    ///
+    /// Every shard is read-locked up front on the calling thread, in the global lock order, and
+    /// each lock is held until every ref yielded from that shard is dropped.
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (SlotMapKey, SlotMapParRef<'_, T>)> {
+        self.shards_in_lock_order()
+            .map(|shard| (shard.index, ParGuard(util::read_shared(&shard.inner))))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .flat_map_iter(|(shard, ParGuard(guard))| {
+                let guard = Arc::new(guard);
+                (0..guard.len()).map(move |nth| {
+                    let id = unsafe { guard.get_unchecked_nth_id(nth) };
+                    let generation = unsafe { guard.get_unchecked_generation(id) };
+                    (
+                        SlotMapKey::new(shard, id, generation),
+                        SlotMapParRef {
+                            guard: guard.clone(),
+                            id,
+                        },
+                    )
+                })
+            })
+    }
+
+    /// Creates a parallel iterator over the keys of and mutable references to values in the map.
+    ///
+    /// Every shard is write-locked up front on the calling thread, in the global lock order, and
+    /// each lock is held until every ref yielded from that shard is dropped.
+    pub fn par_iter_mut(
+        &self,
+    ) -> impl ParallelIterator<Item = (SlotMapKey, SlotMapParRefMut<'_, T>)> {
+        self.shards_in_lock_order()
+            .map(|shard| (shard.index, ParGuard(util::write_shared(&shard.inner))))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .flat_map_iter(|(shard, ParGuard(mut guard))| {
+                let values = (0..guard.len())
+                    .map(|nth| {
+                        let id = unsafe { guard.get_unchecked_nth_id(nth) };
+                        let generation = unsafe { guard.get_unchecked_generation(id) };
+                        let key = SlotMapKey::new(shard, id, generation);
+                        (key, NonNull::from(unsafe { guard.get_unchecked_mut(id) }))
+                    })
+                    .collect::<Vec<_>>();
+                let guard = Arc::new(guard);
+                values.into_iter().map(move |(key, value)| {
+                    (
+                        key,
+                        SlotMapParRefMut {
+                            _guard: guard.clone(),
+                            value,
+                        },
+                    )
+                })
+            })
+    }
+
+    /// Calls `f` on every shard in parallel, each holding a read lock for the whole shard.
+    ///
+    /// Every shard is read-locked up front on the calling thread, in the global lock order.
+    pub fn par_for_each_shard<F>(&self, f: F)
+    where
+        F: Fn(SlotMapShardRef<'_, T>) + Send + Sync,
+    {
+        self.shards_in_lock_order()
+            .map(|shard| {
+                ParGuard(SlotMapShardRef {
+                    guard: util::read_shared(&shard.inner),
+                    shard: shard.index,
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .for_each(|ParGuard(shard)| f(shard))
+    }
+}
+
+/// Lock taken on the calling thread and handed to a worker.
+struct ParGuard<G>(G);
+
+/// Immutable reference to a value in a [`SlotMap`], sharing the read lock of its shard with the
+/// other refs yielded by [`SlotMap::par_iter`].
+pub struct SlotMapParRef<'a, T> {
+    guard: Arc<RwLockReadGuard<'a, inner::SlotMap<T>>>,
+    id: usize,
+}
+
+#[reflica::reflica]
+impl<T> SlotMapParRef<'_, T> {
+    fn deref(&self) -> &T {
+        unsafe { self.guard.get_unchecked(self.id) }
+    }
+}
+
+/// Mutable reference to a value in a [`SlotMap`], sharing the write lock of its shard with the
+/// other refs yielded by [`SlotMap::par_iter_mut`].
+pub struct SlotMapParRefMut<'a, T> {
+    _guard: Arc<RwLockWriteGuard<'a, inner::SlotMap<T>>>,
+    value: NonNull<T>,
+}
+
+#[reflica::reflica]
+impl<T> SlotMapParRefMut<'_, T> {
+    fn deref(&self) -> &T {
+        unsafe { self.value.as_ref() }
+    }
+
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T> fmt::Debug for SlotMapParRef<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> fmt::Debug for SlotMapParRefMut<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+unsafe impl<T> Send for SlotMapParRef<'_, T> where T: Send + Sync {}
+unsafe impl<T> Sync for SlotMapParRef<'_, T> where T: Send + Sync {}
+
+unsafe impl<T> Send for SlotMapParRefMut<'_, T> where T: Send {}
+unsafe impl<T> Sync for SlotMapParRefMut<'_, T> where T: Send + Sync {}
+
+unsafe impl<T> Send for ParGuard<RwLockReadGuard<'_, inner::SlotMap<T>>> where T: Send + Sync {}
+unsafe impl<T> Send for ParGuard<RwLockWriteGuard<'_, inner::SlotMap<T>>> where T: Send {}
+unsafe impl<T> Send for ParGuard<SlotMapShardRef<'_, T>> where T: Send + Sync {}
@@ -0,0 +1,171 @@
+use std::{
+    convert::TryFrom,
+    mem::{self, ManuallyDrop},
+    sync::atomic::Ordering,
+};
+
+use crate::{SlotMap, SlotMapId};
+
+pub(crate) const MAX_SHARDS: usize = 1 << SHARD_BITS;
+
+const MAP_BITS: u32 = 10;
+const SHARD_BITS: u32 = 12;
+const GENERATION_BITS: u32 = 10;
+const SLOT_BITS: u32 = 32;
+const MAP_MASK: usize = (1 << MAP_BITS) - 1;
+const SHARD_MASK: usize = MAX_SHARDS - 1;
+const GENERATION_MASK: u32 = (1 << GENERATION_BITS) - 1;
+
+/// Value of an entry's reference count while its owner is encoded as a [`RawKey`].
+pub(crate) const RAW_OWNED: usize = usize::MAX;
+
+/// An owning [`SlotMapId`] encoded as a single `u64`, for passing through FFI or kernel user data.
+///
+/// Created by [`SlotMapId::into_raw`] and turned back into a handle by
+/// [`SlotMap::id_from_raw`] or [`SlotMap::try_id_from_raw`]. While a raw key is outstanding the
+/// value stays in the map; a raw key that is never restored leaks the value until the map is
+/// dropped.
+///
+/// The key packs a tag identifying the map (10 bits), the shard index (12 bits), the low 10 bits
+/// of the slot generation and the slot id (32 bits).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RawKey(u64);
+
+impl RawKey {
+    /// Reconstructs a key from [`to_bits`](Self::to_bits).
+    pub fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the key as a `u64`.
+    pub fn to_bits(self) -> u64 {
+        self.0
+    }
+
+    fn new(map: usize, shard: usize, id: usize, generation: u32) -> Self {
+        let id = u32::try_from(id).expect("slot id does not fit in a raw key");
+        let map = map & MAP_MASK;
+        let generation = generation & GENERATION_MASK;
+        Self(
+            (map as u64) << (SHARD_BITS + GENERATION_BITS + SLOT_BITS)
+                | (shard as u64) << (GENERATION_BITS + SLOT_BITS)
+                | (generation as u64) << SLOT_BITS
+                | id as u64,
+        )
+    }
+
+    fn map(self) -> usize {
+        (self.0 >> (SHARD_BITS + GENERATION_BITS + SLOT_BITS)) as usize
+    }
+
+    fn shard(self) -> usize {
+        (self.0 >> (GENERATION_BITS + SLOT_BITS)) as usize & SHARD_MASK
+    }
+
+    fn generation(self) -> u32 {
+        (self.0 >> SLOT_BITS) as u32 & GENERATION_MASK
+    }
+
+    fn id(self) -> usize {
+        self.0 as u32 as usize
+    }
+}
+
+impl From<RawKey> for u64 {
+    fn from(key: RawKey) -> Self {
+        key.to_bits()
+    }
+}
+
+impl From<u64> for RawKey {
+    fn from(bits: u64) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+impl<T> SlotMapId<T> {
+    /// Releases the handle without removing the value and returns a key that can restore it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot id does not fit in 32 bits.
+    ///
+    /// Time complexity: O(1)
+    pub fn into_raw(mut self) -> RawKey {
+        let key = RawKey::new(self.from.map, self.from.index, self.id, self.generation);
+        let guard = self.lock_read();
+
+        if guard.contains(self.id, self.generation) {
+            unsafe { guard.get_unchecked_refs(self.id) }.store(RAW_OWNED, Ordering::Relaxed);
+        }
+
+        drop(guard);
+        unsafe { ManuallyDrop::drop(&mut self.from) };
+        mem::forget(self);
+        key
+    }
+}
+
+impl<T> SlotMap<T> {
+    /// Restores the handle encoded by [`SlotMapId::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `key` must have been returned by [`SlotMapId::into_raw`] on a handle of this map, must
+    /// not have been restored already, and its value must not have been evicted since.
+    ///
+    /// Time complexity: O(1)
+    pub unsafe fn id_from_raw(&self, key: RawKey) -> SlotMapId<T> {
+        let shard = unsafe { self.shards.get_unchecked(key.shard()) };
+        let guard = shard.inner.read();
+        unsafe { guard.get_unchecked_refs(key.id()) }.store(0, Ordering::Relaxed);
+        let generation = unsafe { guard.get_unchecked_generation(key.id()) };
+        drop(guard);
+
+        SlotMapId {
+            from: ManuallyDrop::new(shard.clone()),
+            id: key.id(),
+            generation,
+        }
+    }
+
+    /// Restores the handle encoded by [`SlotMapId::into_raw`], or returns `None` if the key does
+    /// not refer to a value of this map that is currently released as a raw key.
+    ///
+    /// Rejects keys from other maps, keys that were already restored and keys whose slot has been
+    /// reused. Maps are told apart by a 10-bit tag, so a foreign key can only be mistaken for one
+    /// of this map if the two maps were created a multiple of 1024 maps apart; likewise only the
+    /// low 10 bits of the generation are compared.
+    ///
+    /// Time complexity: O(1)
+    pub fn try_id_from_raw(&self, key: RawKey) -> Option<SlotMapId<T>> {
+        let shard = self.shards.get(key.shard())?;
+
+        if shard.map & MAP_MASK != key.map() {
+            return None;
+        }
+
+        let guard = shard.inner.read();
+
+        if !guard.is_occupied(key.id()) {
+            return None;
+        }
+
+        let generation = unsafe { guard.get_unchecked_generation(key.id()) };
+
+        if generation & GENERATION_MASK != key.generation() {
+            return None;
+        }
+
+        unsafe { guard.get_unchecked_refs(key.id()) }
+            .compare_exchange(RAW_OWNED, 0, Ordering::Relaxed, Ordering::Relaxed)
+            .ok()?;
+        drop(guard);
+
+        Some(SlotMapId {
+            from: ManuallyDrop::new(shard.clone()),
+            id: key.id(),
+            generation,
+        })
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    cell::Cell,
+    collections::hash_map::DefaultHasher,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+/// Strategy used by [`SlotMap::insert`](crate::SlotMap::insert) to pick a shard.
+///
+/// Implemented for the built-in strategies and for any `Fn(&ShardLoads<'_>) -> usize`.
+pub trait ShardSelector: Send + Sync {
+    /// Returns the index of the shard to insert into, which must be less than `loads.len()`.
+    fn select(&self, loads: &ShardLoads<'_>) -> usize;
+}
+
+impl<F> ShardSelector for F
+where
+    F: Fn(&ShardLoads<'_>) -> usize + Send + Sync,
+{
+    fn select(&self, loads: &ShardLoads<'_>) -> usize {
+        self(loads)
+    }
+}
+
+/// Read-only view of the number of entries in each shard, passed to [`ShardSelector::select`].
+pub struct ShardLoads<'a> {
+    len: usize,
+    load: &'a dyn Fn(usize) -> usize,
+}
+
+impl<'a> ShardLoads<'a> {
+    pub(crate) fn new(len: usize, load: &'a dyn Fn(usize) -> usize) -> Self {
+        Self { len, load }
+    }
+
+    /// Returns the number of shards. Always non-zero.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether there are no shards. Always `false`.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of entries in the shard at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= self.len()`.
+    pub fn load(&self, index: usize) -> usize {
+        assert!(index < self.len, "shard index out of range");
+        (self.load)(index)
+    }
+}
+
+impl fmt::Debug for ShardLoads<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..self.len).map(|index| (self.load)(index)))
+            .finish()
+    }
+}
+
+/// Round-robin over the shards, optionally picking the least loaded of several evenly spaced
+/// candidates.
+///
+/// All inserting threads share one counter. [`RoundRobin::new`] and the default sample 4
+/// candidates; [`RoundRobin::strict`] uses the shards strictly in turn.
+#[derive(Debug)]
+pub struct RoundRobin {
+    rr: AtomicUsize,
+    samples: usize,
+}
+
+impl RoundRobin {
+    /// Advances round-robin, then picks the least loaded of 4 evenly spaced shards.
+    ///
+    /// Same as [`RoundRobin::default`].
+    pub fn new() -> Self {
+        Self::sampled(4)
+    }
+
+    /// Uses the shards strictly in turn, ignoring their loads.
+    pub fn strict() -> Self {
+        Self::sampled(1)
+    }
+
+    /// Advances round-robin, then picks the least loaded of `samples` evenly spaced shards.
+    ///
+    /// `samples` is clamped to `1..=` the shard count.
+    pub fn sampled(samples: usize) -> Self {
+        Self {
+            rr: 0.into(),
+            samples: samples.max(1),
+        }
+    }
+}
+
+impl Default for RoundRobin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShardSelector for RoundRobin {
+    fn select(&self, loads: &ShardLoads<'_>) -> usize {
+        let num_shards = loads.len();
+        let samples = self.samples.min(num_shards);
+        let interval = num_shards / samples;
+        let rr = self.rr.fetch_add(1, Ordering::Relaxed);
+        let candidates = (0..samples).map(|i| rr.wrapping_add(i * interval) % num_shards);
+        let min = candidates.min_by_key(|index| loads.load(*index));
+        unsafe { min.unwrap_unchecked() }
+    }
+}
+
+/// Sticks each thread to one shard, assigning threads to shards in the order they first insert.
+///
+/// Maximizes locality when each thread mostly touches its own values; balance depends on the
+/// number of inserting threads.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadAffinity;
+
+impl ShardSelector for ThreadAffinity {
+    fn select(&self, loads: &ShardLoads<'_>) -> usize {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        thread_local! {
+            static ORDINAL: Cell<Option<usize>> = const { Cell::new(None) };
+        }
+
+        let ordinal = ORDINAL.with(|ordinal| {
+            ordinal.get().unwrap_or_else(|| {
+                let next = NEXT.fetch_add(1, Ordering::Relaxed);
+                ordinal.set(Some(next));
+                next
+            })
+        });
+        ordinal % loads.len()
+    }
+}
+
+/// Picks the shard by hashing the current thread's id.
+///
+/// Like [`ThreadAffinity`] each thread always uses the same shard, but the mapping does not
+/// depend on the order in which threads first insert.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadHash;
+
+impl ShardSelector for ThreadHash {
+    fn select(&self, loads: &ShardLoads<'_>) -> usize {
+        thread_local! {
+            static HASH: u64 = {
+                let mut hasher = DefaultHasher::new();
+                thread::current().id().hash(&mut hasher);
+                hasher.finish()
+            };
+        }
+
+        HASH.with(|hash| (*hash % loads.len() as u64) as usize)
+    }
+}
+
+/// Picks the shard with the fewest entries, preferring the lowest index on ties.
+///
+/// Gives the best balance, but reads every shard's length on each insert.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LeastLoaded;
+
+impl ShardSelector for LeastLoaded {
+    fn select(&self, loads: &ShardLoads<'_>) -> usize {
+        let min = (0..loads.len()).min_by_key(|index| loads.load(*index));
+        unsafe { min.unwrap_unchecked() }
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{
+    de::{Error, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{collections::HashMap, fmt, marker::PhantomData};
+
+use crate::{SlotMap, SlotMapId, SlotMapKey};
+
+/// Caps pre-allocation from a length read out of untrusted input, like serde's own impls do.
+pub(crate) fn cautious(size_hint: Option<usize>) -> usize {
+    size_hint.unwrap_or(0).min(4096)
+}
+
+impl<T> Serialize for SlotMap<T>
+where
+    T: Serialize,
+{
+    /// Serializes a consistent snapshot of the map (see [`SlotMap::read_all`]) as a sequence of
+    /// `(key, value)` pairs.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.read_all().iter())
+    }
+}
+
+impl<T> SlotMap<T> {
+    /// Inserts the values serialized from another map and returns their new handles keyed by
+    /// their old keys.
+    ///
+    /// Use this instead of [`SlotMapRestore`] to restore into a map built with
+    /// [`SlotMapBuilder`](crate::SlotMapBuilder).
+    pub fn deserialize_into<'de, D>(
+        &self,
+        deserializer: D,
+    ) -> Result<HashMap<SlotMapKey, SlotMapId<T>>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(Entries {
+            map: self,
+            marker: PhantomData,
+        })
+    }
+}
+
+/// A freshly deserialized [`SlotMap`] together with the handles of its values, keyed by the keys
+/// they were serialized with.
+pub struct SlotMapRestore<T> {
+    map: SlotMap<T>,
+    ids: HashMap<SlotMapKey, SlotMapId<T>>,
+}
+
+impl<T> SlotMapRestore<T> {
+    /// Returns the restored map and the handles keyed by their old keys.
+    pub fn into_parts(self) -> (SlotMap<T>, HashMap<SlotMapKey, SlotMapId<T>>) {
+        (self.map, self.ids)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for SlotMapRestore<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let map = SlotMap::new();
+        let ids = map.deserialize_into(deserializer)?;
+        Ok(Self { map, ids })
+    }
+}
+
+struct Entries<'a, T> {
+    map: &'a SlotMap<T>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'de, T> Visitor<'de> for Entries<'_, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = HashMap<SlotMapKey, SlotMapId<T>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of (key, value) pairs")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut keys = Vec::with_capacity(cautious(seq.size_hint()));
+        let mut values = Vec::with_capacity(keys.capacity());
+
+        while let Some((key, value)) = seq.next_element::<(SlotMapKey, T)>()? {
+            keys.push(key);
+            values.push(value)
+        }
+
+        let mut ids = HashMap::with_capacity(keys.len());
+
+        for (key, id) in keys.into_iter().zip(self.map.insert_many(values)) {
+            if ids.insert(key, id).is_some() {
+                return Err(A::Error::custom(format_args!("duplicate key {:?}", key)));
+            }
+        }
+
+        Ok(ids)
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+use crate::{inner, util::RwLockReadGuard, SlotMap, SlotMapKey};
+
+impl<T> SlotMap<T> {
+    /// Returns a consistent, point-in-time view of the whole map.
+    ///
+    /// Every shard is read-locked for the lifetime of the returned snapshot, so no value can be
+    /// inserted, removed or mutated while it exists. Shards are locked in the global lock order
+    /// (by map, then by shard index) shared by every multi-shard operation, so this cannot
+    /// deadlock against them.
+    ///
+    /// Time complexity: O(s) where s is the number of shards
+    pub fn read_all(&self) -> SlotMapSnapshot<'_, T> {
+        SlotMapSnapshot {
+            guards: self
+                .shards_in_lock_order()
+                .map(|shard| shard.inner.read())
+                .collect(),
+        }
+    }
+}
+
+/// A read-locked view of every shard of a [`SlotMap`] at once.
+///
+/// Created by [`SlotMap::read_all`].
+pub struct SlotMapSnapshot<'a, T> {
+    guards: Box<[RwLockReadGuard<'a, inner::SlotMap<T>>]>,
+}
+
+impl<T> SlotMapSnapshot<'_, T> {
+    /// Returns the number of values in the map.
+    ///
+    /// Time complexity: O(s) where s is the number of shards
+    pub fn len(&self) -> usize {
+        self.guards.iter().map(|guard| guard.len()).sum()
+    }
+
+    /// Returns whether the map is empty.
+    ///
+    /// Time complexity: O(s) where s is the number of shards
+    pub fn is_empty(&self) -> bool {
+        self.guards.iter().all(|guard| guard.len() == 0)
+    }
+
+    /// Returns a reference to the value for `key`, or `None` if it is not in the map.
+    ///
+    /// Time complexity: O(1)
+    pub fn get(&self, key: SlotMapKey) -> Option<&T> {
+        let guard = self.guards.get(key.shard)?;
+        guard
+            .contains(key.id, key.generation)
+            .then(|| unsafe { guard.get_unchecked(key.id) })
+    }
+
+    /// Returns an iterator over the keys of and references to all values in the map.
+    pub fn iter(&self) -> impl Iterator<Item = (SlotMapKey, &T)> {
+        self.guards.iter().enumerate().flat_map(|(shard, guard)| {
+            (0..guard.len()).map(move |index| {
+                let id = unsafe { guard.get_unchecked_nth_id(index) };
+                let generation = unsafe { guard.get_unchecked_generation(id) };
+                (SlotMapKey::new(shard, id, generation), unsafe {
+                    guard.get_unchecked(id)
+                })
+            })
+        })
+    }
+}
+
+impl<T> fmt::Debug for SlotMapSnapshot<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+unsafe impl<T> Send for SlotMapSnapshot<'_, T> where T: Send + Sync {}
+unsafe impl<T> Sync for SlotMapSnapshot<'_, T> where T: Send + Sync {}
@@ -1,5 +1,11 @@
+#[cfg(feature = "deadlock_detection")]
+mod detect;
+mod lock;
 mod shard;
 mod swap;
 
+#[cfg(feature = "deadlock_detection")]
+pub use detect::*;
+pub use lock::*;
 pub use shard::*;
 pub use swap::*;
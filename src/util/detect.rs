@@ -0,0 +1,172 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt, mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::Mode;
+
+thread_local! {
+    static TOKEN: u8 = const { 0 };
+    static READS: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+    static REQUESTER: Cell<Option<*const (dyn fmt::Debug + 'static)>> = const { Cell::new(None) };
+}
+
+#[derive(Clone, Copy)]
+pub enum Label {
+    Unknown,
+    Shard(usize),
+    Heap,
+}
+
+pub struct Tracker {
+    label: Label,
+    writer: AtomicUsize,
+    upgrader: AtomicUsize,
+}
+
+impl Tracker {
+    #[allow(clippy::declare_interior_mutable_const)]
+    pub const INIT: Self = Self::new(Label::Unknown);
+
+    pub const fn new(label: Label) -> Self {
+        Self {
+            label,
+            writer: AtomicUsize::new(0),
+            upgrader: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn waiting(&self, mode: Mode) {
+        let thread = current_thread();
+
+        if self.writer.load(Ordering::Relaxed) == thread {
+            self.deadlock(mode, Mode::Write)
+        }
+
+        match mode {
+            Mode::Read => {}
+            Mode::Upgradable => {
+                if self.upgrader.load(Ordering::Relaxed) == thread {
+                    self.deadlock(mode, Mode::Upgradable)
+                }
+            }
+            Mode::Write => {
+                if self.upgrader.load(Ordering::Relaxed) == thread {
+                    self.deadlock(mode, Mode::Upgradable)
+                }
+
+                self.waiting_upgrade()
+            }
+        }
+    }
+
+    pub fn waiting_upgrade(&self) {
+        let reading = READS
+            .try_with(|reads| reads.borrow().contains(&self.address()))
+            .unwrap_or(false);
+
+        if reading {
+            self.deadlock(Mode::Write, Mode::Read)
+        }
+    }
+
+    pub fn locked(&self, mode: Mode) {
+        match mode {
+            Mode::Read => {
+                let _ = READS.try_with(|reads| reads.borrow_mut().push(self.address()));
+            }
+            Mode::Upgradable => self.upgrader.store(current_thread(), Ordering::Relaxed),
+            Mode::Write => self.writer.store(current_thread(), Ordering::Relaxed),
+        }
+    }
+
+    pub fn unlocking(&self, mode: Mode) {
+        match mode {
+            // A read guard released on another thread than the one that acquired it leaves a
+            // stale entry there, and an untracked one finds nothing to remove.
+            Mode::Read => {
+                let _ = READS.try_with(|reads| {
+                    let mut reads = reads.borrow_mut();
+
+                    if let Some(index) = reads.iter().rposition(|&read| read == self.address()) {
+                        reads.swap_remove(index);
+                    }
+                });
+            }
+            Mode::Upgradable => self.upgrader.store(0, Ordering::Relaxed),
+            Mode::Write => self.writer.store(0, Ordering::Relaxed),
+        }
+    }
+
+    fn address(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    fn deadlock(&self, requested: Mode, held: Mode) -> ! {
+        let requester = REQUESTER.try_with(Cell::get).ok().flatten();
+        let mut message = format!(
+            "deadlock: this thread is waiting for {} on {} while it holds {} on it",
+            self.label.guard(requested),
+            self.label,
+            self.label.guard(held),
+        );
+
+        if let Some(requester) = requester {
+            message += &format!(", requested by {:?}", unsafe { &*requester });
+        }
+
+        panic!("{}", message)
+    }
+}
+
+impl Label {
+    fn guard(self, mode: Mode) -> &'static str {
+        match (self, mode) {
+            (Label::Shard(_), Mode::Read) => {
+                "a read lock (SlotMapRef, SlotMapShardRef or SlotMapSnapshot)"
+            }
+            (Label::Shard(_), Mode::Upgradable) => "an upgradable read lock (SlotMapRefUpgradable)",
+            (Label::Shard(_), Mode::Write) => {
+                "a write lock (SlotMapRefMut, SlotMapShardRefMut or SlotMapManyRefMut)"
+            }
+            (Label::Heap, Mode::Read) => "a read lock (SlotHeapPeek or SlotHeapRef)",
+            (Label::Heap, Mode::Upgradable) => "an upgradable read lock (SlotHeapRefUpgradable)",
+            (Label::Heap, Mode::Write) => "a write lock (SlotHeapPeekMut or SlotHeapRefMut)",
+            (Label::Unknown, Mode::Read) => "a read lock",
+            (Label::Unknown, Mode::Upgradable) => "an upgradable read lock",
+            (Label::Unknown, Mode::Write) => "a write lock",
+        }
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Label::Unknown => f.write_str("a lock"),
+            Label::Shard(index) => write!(f, "SlotMap shard {}", index),
+            Label::Heap => f.write_str("SlotHeap"),
+        }
+    }
+}
+
+pub fn requesting<R>(requester: &dyn fmt::Debug, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<*const (dyn fmt::Debug + 'static)>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let _ = REQUESTER.try_with(|requester| requester.set(self.0));
+        }
+    }
+
+    let requester =
+        unsafe { mem::transmute::<&dyn fmt::Debug, &(dyn fmt::Debug + 'static)>(requester) };
+    let _restore = Restore(REQUESTER.with(|current| current.replace(Some(requester))));
+    f()
+}
+
+fn current_thread() -> usize {
+    TOKEN
+        .try_with(|token| token as *const u8 as usize)
+        .unwrap_or(usize::MAX)
+}
@@ -0,0 +1,473 @@
+use parking_lot::lock_api;
+#[cfg(not(feature = "deadlock_detection"))]
+use std::fmt;
+
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+use parking_lot::lock_api::RawRwLock as _;
+#[cfg(feature = "metrics")]
+use parking_lot::Once;
+#[cfg(feature = "metrics")]
+use std::{
+    cell::UnsafeCell,
+    convert::TryFrom,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "deadlock_detection")]
+use super::{Label, Tracker};
+#[cfg(feature = "metrics")]
+use crate::LockStats;
+
+#[cfg(not(any(feature = "metrics", feature = "deadlock_detection")))]
+pub type RawRwLock = parking_lot::RawRwLock;
+
+pub type RwLock<T> = lock_api::RwLock<RawRwLock, T>;
+pub type RwLockReadGuard<'a, T> = lock_api::RwLockReadGuard<'a, RawRwLock, T>;
+pub type RwLockUpgradableReadGuard<'a, T> = lock_api::RwLockUpgradableReadGuard<'a, RawRwLock, T>;
+pub type RwLockWriteGuard<'a, T> = lock_api::RwLockWriteGuard<'a, RawRwLock, T>;
+
+pub fn shard_lock<T>(index: usize, value: T) -> RwLock<T> {
+    #[cfg(feature = "deadlock_detection")]
+    {
+        RwLock::from_raw(RawRwLock::labeled(Label::Shard(index)), value)
+    }
+
+    #[cfg(not(feature = "deadlock_detection"))]
+    {
+        let _ = index;
+        RwLock::new(value)
+    }
+}
+
+pub fn heap_lock<T>(value: T) -> RwLock<T> {
+    #[cfg(feature = "deadlock_detection")]
+    {
+        RwLock::from_raw(RawRwLock::labeled(Label::Heap), value)
+    }
+
+    #[cfg(not(feature = "deadlock_detection"))]
+    {
+        RwLock::new(value)
+    }
+}
+
+/// Read-locks `lock` for a guard that may be released on another thread, such as one shared by
+/// the refs of a parallel iterator.
+#[cfg(feature = "rayon")]
+pub fn read_shared<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    #[cfg(feature = "deadlock_detection")]
+    unsafe {
+        lock.raw().lock_untracked(
+            Mode::Read,
+            |inner| inner.try_lock_shared(),
+            |inner| inner.lock_shared(),
+        );
+        lock.make_read_guard_unchecked()
+    }
+
+    #[cfg(not(feature = "deadlock_detection"))]
+    lock.read()
+}
+
+/// Write-locks `lock` for a guard that may be released on another thread, such as one shared by
+/// the refs of a parallel iterator.
+#[cfg(feature = "rayon")]
+pub fn write_shared<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    #[cfg(feature = "deadlock_detection")]
+    unsafe {
+        lock.raw().lock_untracked(
+            Mode::Write,
+            |inner| inner.try_lock_exclusive(),
+            |inner| inner.lock_exclusive(),
+        );
+        lock.make_write_guard_unchecked()
+    }
+
+    #[cfg(not(feature = "deadlock_detection"))]
+    lock.write()
+}
+
+#[cfg(not(feature = "deadlock_detection"))]
+pub fn requesting<R>(requester: &dyn fmt::Debug, f: impl FnOnce() -> R) -> R {
+    let _ = requester;
+    f()
+}
+
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+#[derive(Clone, Copy)]
+pub enum Mode {
+    Read,
+    Upgradable,
+    Write,
+}
+
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+pub struct RawRwLock {
+    inner: parking_lot::RawRwLock,
+    #[cfg(feature = "metrics")]
+    metrics: Metrics,
+    #[cfg(feature = "deadlock_detection")]
+    tracker: Tracker,
+}
+
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+impl RawRwLock {
+    #[cfg(feature = "deadlock_detection")]
+    fn labeled(label: Label) -> Self {
+        Self {
+            inner: <parking_lot::RawRwLock as lock_api::RawRwLock>::INIT,
+            #[cfg(feature = "metrics")]
+            metrics: Metrics::INIT,
+            tracker: Tracker::new(label),
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> LockStats {
+        self.metrics.stats()
+    }
+
+    fn lock_with(
+        &self,
+        mode: Mode,
+        try_lock: impl FnOnce(&parking_lot::RawRwLock) -> bool,
+        wait: impl FnOnce(&parking_lot::RawRwLock) -> bool,
+    ) -> bool {
+        if !try_lock(&self.inner) {
+            #[cfg(feature = "metrics")]
+            self.metrics.contended(mode);
+
+            if !wait(&self.inner) {
+                return false;
+            }
+        }
+
+        self.locked(mode);
+        true
+    }
+
+    /// Locks without attributing the lock to this thread, so the deadlock check neither relies on
+    /// nor blames it. Releasing it from another thread is then harmless to the tracker.
+    #[cfg(all(feature = "deadlock_detection", feature = "rayon"))]
+    fn lock_untracked(
+        &self,
+        mode: Mode,
+        try_lock: impl FnOnce(&parking_lot::RawRwLock) -> bool,
+        lock: impl FnOnce(&parking_lot::RawRwLock),
+    ) {
+        if !try_lock(&self.inner) {
+            #[cfg(feature = "metrics")]
+            self.metrics.contended(mode);
+
+            lock(&self.inner)
+        }
+
+        #[cfg(feature = "metrics")]
+        self.metrics.locked(mode);
+        #[cfg(not(feature = "metrics"))]
+        let _ = mode;
+    }
+
+    fn try_lock_with(
+        &self,
+        mode: Mode,
+        try_lock: impl FnOnce(&parking_lot::RawRwLock) -> bool,
+    ) -> bool {
+        let locked = try_lock(&self.inner);
+
+        if locked {
+            self.locked(mode)
+        }
+
+        locked
+    }
+
+    fn waiting(&self, mode: Mode) {
+        #[cfg(feature = "deadlock_detection")]
+        self.tracker.waiting(mode);
+        #[cfg(not(feature = "deadlock_detection"))]
+        let _ = mode;
+    }
+
+    fn locked(&self, mode: Mode) {
+        #[cfg(feature = "metrics")]
+        self.metrics.locked(mode);
+        #[cfg(feature = "deadlock_detection")]
+        self.tracker.locked(mode);
+    }
+
+    unsafe fn unlocking(&self, mode: Mode) {
+        #[cfg(feature = "metrics")]
+        unsafe {
+            self.metrics.unlocking(mode)
+        }
+        #[cfg(feature = "deadlock_detection")]
+        self.tracker.unlocking(mode);
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+unsafe impl lock_api::RawRwLock for RawRwLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = Self {
+        inner: <parking_lot::RawRwLock as lock_api::RawRwLock>::INIT,
+        #[cfg(feature = "metrics")]
+        metrics: Metrics::INIT,
+        #[cfg(feature = "deadlock_detection")]
+        tracker: Tracker::INIT,
+    };
+
+    type GuardMarker = <parking_lot::RawRwLock as lock_api::RawRwLock>::GuardMarker;
+
+    fn lock_shared(&self) {
+        self.lock_with(
+            Mode::Read,
+            |inner| inner.try_lock_shared(),
+            |inner| {
+                self.waiting(Mode::Read);
+                inner.lock_shared();
+                true
+            },
+        );
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        self.try_lock_with(Mode::Read, |inner| inner.try_lock_shared())
+    }
+
+    unsafe fn unlock_shared(&self) {
+        unsafe {
+            self.unlocking(Mode::Read);
+            self.inner.unlock_shared()
+        }
+    }
+
+    fn lock_exclusive(&self) {
+        self.lock_with(
+            Mode::Write,
+            |inner| inner.try_lock_exclusive(),
+            |inner| {
+                self.waiting(Mode::Write);
+                inner.lock_exclusive();
+                true
+            },
+        );
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        self.try_lock_with(Mode::Write, |inner| inner.try_lock_exclusive())
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        unsafe {
+            self.unlocking(Mode::Write);
+            self.inner.unlock_exclusive()
+        }
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+unsafe impl lock_api::RawRwLockDowngrade for RawRwLock {
+    unsafe fn downgrade(&self) {
+        unsafe {
+            self.unlocking(Mode::Write);
+            self.inner.downgrade()
+        }
+
+        self.locked(Mode::Read)
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+unsafe impl lock_api::RawRwLockUpgrade for RawRwLock {
+    fn lock_upgradable(&self) {
+        self.lock_with(
+            Mode::Upgradable,
+            |inner| inner.try_lock_upgradable(),
+            |inner| {
+                self.waiting(Mode::Upgradable);
+                inner.lock_upgradable();
+                true
+            },
+        );
+    }
+
+    fn try_lock_upgradable(&self) -> bool {
+        self.try_lock_with(Mode::Upgradable, |inner| inner.try_lock_upgradable())
+    }
+
+    unsafe fn unlock_upgradable(&self) {
+        unsafe {
+            self.unlocking(Mode::Upgradable);
+            self.inner.unlock_upgradable()
+        }
+    }
+
+    unsafe fn upgrade(&self) {
+        self.lock_with(
+            Mode::Write,
+            |inner| unsafe { inner.try_upgrade() },
+            |inner| {
+                #[cfg(feature = "deadlock_detection")]
+                self.tracker.waiting_upgrade();
+                unsafe { inner.upgrade() };
+                true
+            },
+        );
+        unsafe { self.unlocking(Mode::Upgradable) }
+    }
+
+    unsafe fn try_upgrade(&self) -> bool {
+        let upgraded = self.try_lock_with(Mode::Write, |inner| unsafe { inner.try_upgrade() });
+
+        if upgraded {
+            unsafe { self.unlocking(Mode::Upgradable) }
+        }
+
+        upgraded
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+unsafe impl lock_api::RawRwLockTimed for RawRwLock {
+    type Duration = <parking_lot::RawRwLock as lock_api::RawRwLockTimed>::Duration;
+    type Instant = <parking_lot::RawRwLock as lock_api::RawRwLockTimed>::Instant;
+
+    fn try_lock_shared_for(&self, timeout: Self::Duration) -> bool {
+        self.lock_with(
+            Mode::Read,
+            |inner| inner.try_lock_shared(),
+            |inner| inner.try_lock_shared_for(timeout),
+        )
+    }
+
+    fn try_lock_shared_until(&self, timeout: Self::Instant) -> bool {
+        self.lock_with(
+            Mode::Read,
+            |inner| inner.try_lock_shared(),
+            |inner| inner.try_lock_shared_until(timeout),
+        )
+    }
+
+    fn try_lock_exclusive_for(&self, timeout: Self::Duration) -> bool {
+        self.lock_with(
+            Mode::Write,
+            |inner| inner.try_lock_exclusive(),
+            |inner| inner.try_lock_exclusive_for(timeout),
+        )
+    }
+
+    fn try_lock_exclusive_until(&self, timeout: Self::Instant) -> bool {
+        self.lock_with(
+            Mode::Write,
+            |inner| inner.try_lock_exclusive(),
+            |inner| inner.try_lock_exclusive_until(timeout),
+        )
+    }
+}
+
+#[cfg(feature = "metrics")]
+struct Metrics {
+    reads: AtomicU64,
+    writes: AtomicU64,
+    contended_reads: AtomicU64,
+    contended_writes: AtomicU64,
+    max_read_hold: AtomicU64,
+    max_write_hold: AtomicU64,
+    readers: AtomicUsize,
+    read_since: AtomicU64,
+    write_since: UnsafeCell<MaybeUninit<Instant>>,
+}
+
+#[cfg(feature = "metrics")]
+impl Metrics {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = Self {
+        reads: AtomicU64::new(0),
+        writes: AtomicU64::new(0),
+        contended_reads: AtomicU64::new(0),
+        contended_writes: AtomicU64::new(0),
+        max_read_hold: AtomicU64::new(0),
+        max_write_hold: AtomicU64::new(0),
+        readers: AtomicUsize::new(0),
+        read_since: AtomicU64::new(u64::MAX),
+        write_since: UnsafeCell::new(MaybeUninit::uninit()),
+    };
+
+    fn stats(&self) -> LockStats {
+        LockStats {
+            reads: self.reads.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+            contended_reads: self.contended_reads.load(Ordering::Relaxed),
+            contended_writes: self.contended_writes.load(Ordering::Relaxed),
+            max_read_hold: Duration::from_nanos(self.max_read_hold.load(Ordering::Relaxed)),
+            max_write_hold: Duration::from_nanos(self.max_write_hold.load(Ordering::Relaxed)),
+        }
+    }
+
+    fn contended(&self, mode: Mode) {
+        match mode {
+            Mode::Read | Mode::Upgradable => &self.contended_reads,
+            Mode::Write => &self.contended_writes,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+    }
+
+    // A read span starts at the earliest acquisition among its readers and ends when the last
+    // one leaves. A span starting just as another ends may go unrecorded, never overstated.
+    fn locked(&self, mode: Mode) {
+        match mode {
+            Mode::Read | Mode::Upgradable => {
+                self.reads.fetch_add(1, Ordering::Relaxed);
+                self.read_since.fetch_min(nanos(), Ordering::AcqRel);
+                self.readers.fetch_add(1, Ordering::AcqRel);
+            }
+            Mode::Write => {
+                self.writes.fetch_add(1, Ordering::Relaxed);
+                unsafe { (*self.write_since.get()).write(Instant::now()) };
+            }
+        }
+    }
+
+    unsafe fn unlocking(&self, mode: Mode) {
+        match mode {
+            Mode::Read | Mode::Upgradable => {
+                if self.readers.fetch_sub(1, Ordering::AcqRel) == 1 {
+                    let since = self.read_since.swap(u64::MAX, Ordering::AcqRel);
+                    let held = nanos().saturating_sub(since);
+                    self.max_read_hold.fetch_max(held, Ordering::Relaxed);
+                }
+            }
+            Mode::Write => {
+                let since = unsafe { (*self.write_since.get()).assume_init_read() };
+                let held = u64::try_from(since.elapsed().as_nanos()).unwrap_or(u64::MAX);
+                self.max_write_hold.fetch_max(held, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Nanoseconds since the first call, as a read span start that fits in an atomic.
+#[cfg(feature = "metrics")]
+fn nanos() -> u64 {
+    struct Epoch(UnsafeCell<MaybeUninit<Instant>>);
+
+    unsafe impl Sync for Epoch {}
+
+    static EPOCH: Epoch = Epoch(UnsafeCell::new(MaybeUninit::uninit()));
+    static ONCE: Once = Once::new();
+
+    ONCE.call_once(|| unsafe {
+        (*EPOCH.0.get()).write(Instant::now());
+    });
+
+    let epoch = unsafe { *(*EPOCH.0.get()).assume_init_ref() };
+    u64::try_from(epoch.elapsed().as_nanos()).unwrap_or(u64::MAX)
+}
+
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+unsafe impl Send for RawRwLock {}
+#[cfg(any(feature = "metrics", feature = "deadlock_detection"))]
+unsafe impl Sync for RawRwLock {}
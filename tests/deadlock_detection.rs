@@ -0,0 +1,104 @@
+#![cfg(feature = "deadlock_detection")]
+
+use deadlock::{check_deadlock, SlotHeap, SlotMapBuilder};
+use std::thread;
+
+#[test]
+#[should_panic(expected = "waiting for a write lock (SlotMapRefMut")]
+fn slotmap_get_mut_while_iterating_same_shard_panics() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let id = map.insert(1);
+    let _ids = map.insert_many(2..5);
+
+    for (_, value) in map.iter() {
+        if *value == 1 {
+            *id.get_mut() += 1
+        }
+    }
+}
+
+#[test]
+fn slotmap_panic_names_shard_and_handle() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let id = map.insert(1);
+    let guard = id.get();
+
+    let message = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        id.get_mut();
+    }))
+    .unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+
+    assert!(message.starts_with("deadlock: "));
+    assert!(message.contains("on SlotMap shard 0"));
+    assert!(message.contains("holds a read lock (SlotMapRef"));
+    assert!(message.contains("requested by SlotMapId"));
+    drop(guard)
+}
+
+#[test]
+#[should_panic(expected = "on SlotHeap while it holds a read lock (SlotHeapPeek")]
+fn slotheap_get_mut_while_peeking_panics() {
+    let heap = SlotHeap::new();
+    let (id, _) = heap.insert(1);
+    let _peek = heap.peek();
+    id.get_mut();
+}
+
+#[test]
+fn upgrades_downgrades_and_nested_reads_do_not_panic() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let ids = map.insert_many(0..4);
+
+    let mut guard = ids[0].get_upgradable().upgrade();
+    *guard += 1;
+    let guard = guard.downgrade();
+    let other = ids[1].get();
+    assert_eq!(*guard + *other, 2);
+    drop((guard, other));
+
+    *ids[1].get_mut() += 1;
+    let heap = SlotHeap::new();
+    let (id, _) = heap.insert(1);
+    *id.get_upgradable().upgrade() += 1;
+    assert_eq!(*heap.peek().unwrap(), 2);
+
+    assert!(check_deadlock().is_empty())
+}
+
+#[test]
+fn guard_released_on_another_thread_frees_the_lock() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let id = map.insert(1);
+
+    let guard = id.get_mut();
+    thread::scope(|scope| {
+        scope.spawn(move || drop(guard));
+    });
+    *id.get_mut() += 1;
+
+    let guard = id.get_upgradable();
+    thread::scope(|scope| {
+        scope.spawn(move || drop(guard));
+    });
+    assert_eq!(*id.get_upgradable().upgrade(), 2);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_refs_are_not_attributed_to_any_thread() {
+    use rayon::prelude::*;
+
+    let map = SlotMapBuilder::new().num_shards(4).build().unwrap();
+    let ids = map.insert_many(0..256);
+
+    let refs = map.par_iter_mut().map(|(_, r)| r).collect::<Vec<_>>();
+    refs.into_par_iter().for_each(|mut r| *r += 1);
+    let refs = map.par_iter().map(|(_, r)| r).collect::<Vec<_>>();
+    refs.into_par_iter().for_each(drop);
+
+    for (i, id) in ids.iter().enumerate() {
+        *id.get_mut() -= 1;
+        assert_eq!(*id.get(), i)
+    }
+}
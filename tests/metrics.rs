@@ -0,0 +1,78 @@
+#![cfg(feature = "metrics")]
+
+use deadlock::{SlotHeap, SlotMapBuilder};
+use std::{sync::Arc, thread, time::Duration};
+
+#[test]
+fn slotmap_stats_track_occupancy_and_lock_waits() {
+    let map = Arc::new(SlotMapBuilder::new().num_shards(2).build().unwrap());
+    let mut ids = map.insert_many(0..10);
+    ids.truncate(6);
+
+    let stats = map.stats();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats.iter().map(|shard| shard.len).sum::<usize>(), 6);
+    assert_eq!(stats.iter().map(|shard| shard.inserts).sum::<u64>(), 10);
+    assert_eq!(stats.iter().map(|shard| shard.removes).sum::<u64>(), 4);
+
+    for shard in &stats {
+        assert_eq!(shard.free, shard.capacity - shard.len);
+        assert!(shard.lock.writes >= shard.removes);
+        assert_eq!(shard.lock.contended_reads, 0)
+    }
+
+    let id = ids.pop().unwrap();
+    let guard = id.get_mut();
+    let reader = map.clone();
+    let handle = thread::spawn(move || reader.stats().len());
+    thread::sleep(Duration::from_millis(50));
+    drop(guard);
+    handle.join().unwrap();
+
+    let stats = map.stats();
+    let shard = stats
+        .iter()
+        .find(|shard| shard.lock.contended_reads != 0)
+        .unwrap();
+    assert!(shard.lock.max_write_hold >= Duration::from_millis(50));
+    drop(id)
+}
+
+#[test]
+fn slotmap_stats_track_read_holds() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let id = map.insert(0);
+
+    let first = id.get();
+    thread::sleep(Duration::from_millis(30));
+    let second = id.get_upgradable();
+    drop(first);
+    thread::sleep(Duration::from_millis(30));
+    drop(second);
+
+    let lock = map.stats()[0].lock;
+    assert!(lock.max_read_hold >= Duration::from_millis(60));
+    assert!(lock.max_write_hold < Duration::from_millis(30))
+}
+
+#[test]
+fn slotheap_stats_count_sifts_and_comparisons() {
+    let heap = SlotHeap::new();
+    let mut ids = (0..8).rev().map(|x| heap.insert(x).0).collect::<Vec<_>>();
+
+    let stats = heap.stats();
+    assert_eq!(stats.len, 8);
+    assert_eq!(stats.inserts, 8);
+    assert_eq!(stats.sift_ups, 13);
+    assert_eq!(stats.sift_downs, 0);
+    assert!(stats.comparisons >= stats.sift_ups);
+    assert_eq!(stats.lock.writes, 8);
+
+    drop(ids.pop());
+
+    let stats = heap.stats();
+    assert_eq!(stats.len, 7);
+    assert_eq!(stats.removes, 1);
+    assert!(stats.sift_downs > 0);
+    assert_eq!(*heap.peek().unwrap(), 1);
+}
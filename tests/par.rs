@@ -0,0 +1,82 @@
+#![cfg(feature = "rayon")]
+
+use deadlock::{
+    SlotHeap, SlotHeapParRef, SlotMap, SlotMapParRef, SlotMapParRefMut, SlotMapTryGetError,
+};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn _par_send_sync_checks() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<SlotMapParRef<'_, i32>>();
+    assert_send_sync::<SlotMapParRefMut<'_, i32>>();
+    assert_send_sync::<SlotHeapParRef<'_, i32>>();
+}
+
+#[test]
+fn par_iter_visits_every_entry_with_its_key() {
+    let map = SlotMap::new();
+    let ids = map.insert_many(0..1000);
+
+    let sum = map
+        .par_iter()
+        .map(|(key, r)| {
+            assert_eq!(key, ids[*r].key());
+            *r
+        })
+        .sum::<usize>();
+    assert_eq!(sum, (0..1000).sum())
+}
+
+#[test]
+fn par_iter_mut_modifies_values() {
+    let map = SlotMap::new();
+    let ids = map.insert_many(0..1000);
+
+    map.par_iter_mut().for_each(|(_, mut r)| *r *= 2);
+
+    for (i, id) in ids.iter().enumerate() {
+        assert_eq!(*id.get(), i * 2)
+    }
+}
+
+#[test]
+fn par_iter_mut_locks_every_shard_up_front() {
+    let map = SlotMap::new();
+    let ids = map.insert_many(0..1000);
+
+    let iter = map.par_iter_mut();
+
+    for id in &ids {
+        assert_eq!(id.try_get().err(), Some(SlotMapTryGetError::Locked))
+    }
+
+    iter.for_each(|(_, mut r)| *r += 1);
+    assert_eq!(*ids[0].try_get().unwrap(), 1);
+}
+
+#[test]
+fn par_for_each_shard_locks_each_shard_once() {
+    let map = SlotMap::new();
+    let _ids = map.insert_many(0..1000);
+    let shards = AtomicUsize::new(0);
+    let values = AtomicUsize::new(0);
+
+    map.par_for_each_shard(|shard| {
+        shards.fetch_add(1, Ordering::Relaxed);
+        values.fetch_add(shard.iter().count(), Ordering::Relaxed);
+    });
+
+    assert_eq!(shards.into_inner(), map.num_shards());
+    assert_eq!(values.into_inner(), 1000)
+}
+
+#[test]
+fn heap_par_iter_visits_every_element() {
+    let heap = SlotHeap::new();
+    let _ids = (0..1000).map(|i| heap.insert(i).0).collect::<Vec<_>>();
+
+    assert_eq!(heap.par_iter().map(|r| *r).sum::<i32>(), (0..1000).sum());
+    assert_eq!(heap.par_iter().filter(|r| r.is_top()).count(), 1)
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "serde")]
+
+use deadlock::{SlotHeap, SlotHeapRestore, SlotMap, SlotMapBuilder, SlotMapRestore};
+use serde::{
+    de::value::{Error, SeqDeserializer},
+    Deserialize,
+};
+
+/// An empty sequence that claims to be huge, like a corrupted length prefix.
+struct Lying;
+
+impl Iterator for Lying {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, Some(usize::MAX))
+    }
+}
+
+#[test]
+fn slotmap_round_trip_reattaches_handles() {
+    let map = SlotMap::new();
+    let ids = map.insert_many((0..32).map(|i| format!("value {}", i)));
+    drop(ids.into_iter().step_by(2).collect::<Vec<_>>());
+
+    let json = serde_json::to_string(&map).unwrap();
+    let ids = map.iter().map(|(key, _)| key).collect::<Vec<_>>();
+
+    let (restored, handles) = serde_json::from_str::<SlotMapRestore<String>>(&json)
+        .unwrap()
+        .into_parts();
+    assert_eq!(restored.len(), map.len());
+    assert_eq!(handles.len(), map.len());
+
+    for key in ids {
+        assert_eq!(*handles[&key].get(), *map.get(key).unwrap())
+    }
+
+    drop(handles);
+    assert!(restored.is_empty())
+}
+
+#[test]
+fn slotmap_deserialize_into_builder_map_rejects_duplicates() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let _ids = map.insert_many(0..4);
+    let json = serde_json::to_string(&map).unwrap();
+
+    let target = SlotMapBuilder::new().num_shards(2).build::<i32>().unwrap();
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    let handles = target.deserialize_into(&mut deserializer).unwrap();
+    assert_eq!(target.len(), 4);
+    assert_eq!(handles.len(), 4);
+
+    let duplicated = format!("[{0},{0}]", &json[1..json.find(']').unwrap() + 1]);
+    assert!(serde_json::from_str::<SlotMapRestore<i32>>(&duplicated).is_err())
+}
+
+#[test]
+fn slotheap_round_trip_keeps_heap_order() {
+    let heap = SlotHeap::new();
+    let ids = (0..16).rev().map(|i| heap.insert(i).0).collect::<Vec<_>>();
+    let keys = ids
+        .iter()
+        .map(|id| (id.key(), *id.get()))
+        .collect::<Vec<_>>();
+
+    let json = serde_json::to_string(&heap).unwrap();
+    let (restored, handles) = serde_json::from_str::<SlotHeapRestore<i32>>(&json)
+        .unwrap()
+        .into_parts();
+    assert_eq!(restored.len(), 16);
+    assert_eq!(*restored.peek().unwrap(), 0);
+
+    for (key, value) in keys {
+        assert_eq!(*handles[&key].get(), value)
+    }
+}
+
+#[test]
+fn huge_length_hint_does_not_preallocate() {
+    let (map, handles) =
+        SlotMapRestore::<u64>::deserialize(SeqDeserializer::<_, Error>::new(Lying))
+            .unwrap()
+            .into_parts();
+    assert!(map.is_empty() && handles.is_empty());
+
+    let (heap, handles) =
+        SlotHeapRestore::<u64>::deserialize(SeqDeserializer::<_, Error>::new(Lying))
+            .unwrap()
+            .into_parts();
+    assert!(heap.is_empty() && handles.is_empty())
+}
@@ -1,19 +1,27 @@
-use deadlock::{SlotHeap, SlotHeapId, SlotHeapPeek, SlotHeapPeekMut, SlotHeapRef, SlotHeapRefMut};
+use deadlock::{
+    MappedSlotHeapRef, MappedSlotHeapRefMut, SlotHeap, SlotHeapId, SlotHeapPeek, SlotHeapPeekMut,
+    SlotHeapRef, SlotHeapRefMut, SlotHeapRefUpgradable, SlotHeapSharedId,
+};
 use std::{
     collections::HashSet,
     sync::{Arc, Mutex},
     thread,
+    time::{Duration, Instant},
 };
 
-fn _slotheap_send_sync_checks<'a>() {
+fn _slotheap_send_sync_checks() {
     fn assert_send_sync<T: Send + Sync>() {}
 
     assert_send_sync::<SlotHeap<i32>>();
     assert_send_sync::<SlotHeapId<i32>>();
-    assert_send_sync::<SlotHeapPeek<'a, i32>>();
-    assert_send_sync::<SlotHeapPeekMut<'a, i32>>();
-    assert_send_sync::<SlotHeapRef<'a, i32>>();
-    assert_send_sync::<SlotHeapRefMut<'a, i32>>();
+    assert_send_sync::<SlotHeapSharedId<i32>>();
+    assert_send_sync::<SlotHeapPeek<'_, i32>>();
+    assert_send_sync::<SlotHeapPeekMut<'_, i32>>();
+    assert_send_sync::<SlotHeapRef<'_, i32>>();
+    assert_send_sync::<SlotHeapRefUpgradable<'_, i32>>();
+    assert_send_sync::<SlotHeapRefMut<'_, i32>>();
+    assert_send_sync::<MappedSlotHeapRef<'_, (i32, i32), i32>>();
+    assert_send_sync::<MappedSlotHeapRefMut<'_, (i32, i32), i32>>();
 }
 
 #[test]
@@ -226,6 +234,179 @@ fn insert_returns_is_top_when_new_min() {
     assert_eq!(*heap.peek().unwrap(), 3);
 }
 
+#[test]
+fn with_capacity_and_reserve_avoid_reallocation() {
+    let heap = SlotHeap::with_capacity(16);
+    assert!(heap.capacity() >= 16);
+
+    let ids = (0..16).map(|i| heap.insert(i).0).collect::<Vec<_>>();
+    let capacity = heap.capacity();
+
+    heap.reserve(16);
+    assert!(heap.capacity() >= 32);
+    assert!(heap.capacity() >= capacity);
+    assert_eq!(*heap.peek().unwrap(), 0);
+    drop(ids);
+    assert!(heap.is_empty())
+}
+
+#[test]
+fn shared_id_removes_on_last_drop_and_reheapifies() {
+    let heap = SlotHeap::new();
+    let (id0, top) = heap.insert_shared(1);
+    assert!(top);
+    let (_id1, _) = heap.insert(2);
+    let clone = id0.clone();
+    assert_eq!(clone.strong_count(), 2);
+
+    *clone.get_mut() = 3;
+    assert_eq!(*heap.peek().unwrap(), 2);
+
+    drop(id0);
+    assert_eq!(heap.len(), 2);
+    assert_eq!(clone.try_unwrap().unwrap(), (3, false));
+    assert_eq!(heap.len(), 1)
+}
+
+#[test]
+fn into_shared_keeps_value_in_place() {
+    let heap = SlotHeap::new();
+    let (id, _) = heap.insert(4);
+    let shared = id.into_shared();
+    let other = shared.clone();
+
+    assert!(shared.get().is_top());
+    let shared = shared.try_unwrap().unwrap_err();
+    drop(other);
+    drop(shared);
+    assert!(heap.is_empty())
+}
+
+#[test]
+fn try_and_timed_access_give_up_on_contention() {
+    let heap = SlotHeap::new();
+    assert!(heap.try_peek().unwrap().is_none());
+    assert!(heap.try_peek_mut().unwrap().is_none());
+
+    let (id0, _) = heap.insert(0);
+    let (id1, _) = heap.insert(1);
+
+    let r = id0.get();
+    assert_eq!(*id1.try_get().unwrap(), 1);
+    assert_eq!(*heap.try_peek().unwrap().unwrap(), 0);
+    assert!(id1.try_get_mut().is_none());
+    assert!(id1.get_mut_for(Duration::from_millis(10)).is_none());
+    assert!(id1
+        .get_mut_until(Instant::now() + Duration::from_millis(10))
+        .is_none());
+    assert!(heap.try_peek_mut().is_none());
+    assert!(heap.peek_mut_for(Duration::from_millis(10)).is_none());
+    drop(r);
+
+    let w = id0.get_mut();
+    assert!(id1.try_get().is_none());
+    assert!(id1.get_for(Duration::from_millis(10)).is_none());
+    assert!(id1
+        .get_until(Instant::now() + Duration::from_millis(10))
+        .is_none());
+    assert!(heap.try_peek().is_none());
+    assert!(heap
+        .peek_until(Instant::now() + Duration::from_millis(10))
+        .is_none());
+    drop(w);
+
+    *heap.peek_mut_until(Instant::now()).unwrap().unwrap() = 2;
+    assert_eq!(
+        *heap.peek_for(Duration::from_millis(10)).unwrap().unwrap(),
+        1
+    );
+    assert!(id0.try_get_mut().is_some());
+}
+
+#[test]
+fn upgrade_then_downgrade_reheapifies() {
+    let heap = SlotHeap::new();
+    let (id0, _) = heap.insert(1);
+    let (id1, _) = heap.insert(2);
+
+    let upgradable = id1.get_upgradable();
+    assert!(!upgradable.is_top());
+    assert_eq!(*id0.try_get().unwrap(), 1);
+
+    let mut r = upgradable.upgrade();
+    *r = 0;
+    let r = r.downgrade();
+    assert!(r.is_top());
+    assert_eq!(*heap.try_peek().unwrap().unwrap(), 0);
+    assert!(!id0.try_get().unwrap().is_top());
+}
+
+#[test]
+fn finish_releases_lock() {
+    let heap = SlotHeap::new();
+    let (id0, _) = heap.insert(1);
+    let (_id1, _) = heap.insert(2);
+
+    let mut r = id0.get_mut();
+    *r = 3;
+    assert!(!r.finish());
+    assert_eq!(*heap.try_peek().unwrap().unwrap(), 2);
+
+    let mut p = heap.peek_mut().unwrap();
+    *p = 4;
+    assert!(!p.finish());
+    assert_eq!(*heap.try_peek().unwrap().unwrap(), 3);
+}
+
+#[test]
+fn map_projects_guard_and_reheapifies_on_drop() {
+    let heap = SlotHeap::new();
+    let (id0, _) = heap.insert((1, "one"));
+    let (id1, _) = heap.insert((2, "two"));
+
+    assert_eq!(
+        *SlotHeapPeek::map(heap.peek().unwrap(), |(_, name)| name),
+        "one"
+    );
+    assert_eq!(*SlotHeapRef::map(id1.get(), |(_, name)| name), "two");
+
+    {
+        let mut priority = SlotHeapRefMut::map(id1.get_mut(), |(priority, _)| priority);
+        *priority = 0
+    }
+
+    assert!(id1.get().is_top());
+
+    {
+        let p = SlotHeapPeekMut::filter_map(heap.peek_mut().unwrap(), |_| None::<&mut i32>);
+        let mut priority = SlotHeapPeekMut::map(p.err().unwrap(), |(priority, _)| priority);
+        *priority = 3
+    }
+
+    assert!(id0.get().is_top());
+    let r = SlotHeapRef::try_map(id0.get(), |_| Err::<&i32, _>(()))
+        .err()
+        .unwrap()
+        .0;
+    let name = MappedSlotHeapRef::map(SlotHeapRef::map(r, |(_, name)| name), |name| &name[..1]);
+    assert_eq!(&*name, "o");
+}
+
+#[test]
+fn debug_shows_values_in_heap_order() {
+    let heap = SlotHeap::new();
+    let (id, _) = heap.insert(3);
+    let (_other, _) = heap.insert(1);
+
+    assert_eq!(format!("{:?}", heap), "SlotHeap { values: [1, 3] }");
+    assert_eq!(format!("{:?}", id), "SlotHeapId { id: 0, generation: 0 }");
+    assert_eq!(format!("{:?}", heap.peek().unwrap()), "1");
+
+    let guard = id.get_mut();
+    assert_eq!(format!("{:?}", heap), "SlotHeap { values: <locked> }");
+    assert_eq!(format!("{:?}", guard), "3");
+}
+
 #[test]
 fn send_sync_multi_threaded_insert() {
     let heap = Arc::new(SlotHeap::new());
@@ -272,3 +453,42 @@ fn send_sync_multi_threaded_peek() {
         handle.join().unwrap()
     }
 }
+
+#[test]
+fn handles_compare_by_heap_and_slot() {
+    let heap = SlotHeap::new();
+    let other = SlotHeap::new();
+    let mut ids = (0..16).map(|x| heap.insert(x).0).collect::<Vec<_>>();
+    let foreign = other.insert(0).0;
+
+    assert!(ids.iter().all(|id| heap.contains(id)));
+    assert!(!heap.contains(&foreign));
+    assert!(other.contains(&foreign));
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 16);
+
+    ids.reverse();
+    ids.sort();
+    assert!(ids.windows(2).all(|pair| pair[0].key() < pair[1].key()));
+
+    let shared = ids.pop().unwrap().into_shared();
+    assert!(shared == shared.clone());
+    assert_ne!(shared.clone(), heap.insert_shared(16).0)
+}
+
+#[test]
+fn move_to_transfers_element_between_heaps() {
+    let heap = SlotHeap::new();
+    let other = SlotHeap::new();
+    let _ids = [heap.insert(1).0, heap.insert(3).0, other.insert(2).0];
+    let id = heap.insert(0).0;
+
+    let id = id.move_to(&other);
+    assert!(other.contains(&id));
+    assert_eq!((heap.len(), other.len()), (2, 2));
+    assert_eq!(*heap.peek().unwrap(), 1);
+    assert_eq!(*other.peek().unwrap(), 0);
+
+    let id = id.move_to(&other);
+    assert_eq!(id.into_inner(), (0, true));
+    assert_eq!(*other.peek().unwrap(), 2);
+}
@@ -0,0 +1,63 @@
+use deadlock::{SlotLockMap, SlotLockMapId, SlotLockMapRef, SlotLockMapRefMut, SlotMapBuilder};
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+fn _slotlockmap_send_sync_checks() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<SlotLockMap<i32>>();
+    assert_send_sync::<SlotLockMapId<i32>>();
+    assert_send_sync::<SlotLockMapRef<'_, i32>>();
+    assert_send_sync::<SlotLockMapRefMut<'_, i32>>()
+}
+
+#[test]
+fn insert_get_into_inner() {
+    let map = SlotLockMap::new();
+    let ids = (0..32).map(|i| map.insert(i)).collect::<Vec<_>>();
+    assert_eq!(map.len(), 32);
+
+    for (i, id) in ids.iter().enumerate() {
+        assert_eq!(*id.get(), i)
+    }
+
+    *ids[3].get_mut() = 100;
+
+    let values = ids
+        .into_iter()
+        .map(SlotLockMapId::into_inner)
+        .collect::<Vec<_>>();
+    assert_eq!(values[3], 100);
+    assert!(map.is_empty())
+}
+
+#[test]
+fn held_get_mut_does_not_block_same_shard() {
+    let map = Arc::new(SlotMapBuilder::new().num_shards(1).build_locked().unwrap());
+    let id0 = map.insert(0);
+    let id1 = Arc::new(map.insert(1));
+    let mut guard = id0.get_mut();
+
+    let (tx, rx) = mpsc::channel();
+    let handle = {
+        let map = map.clone();
+        let id1 = id1.clone();
+
+        thread::spawn(move || {
+            *id1.get_mut() += 10;
+            let id2 = map.insert(2);
+            tx.send(*id1.get() + *id2.get()).unwrap();
+        })
+    };
+
+    assert_eq!(rx.recv_timeout(Duration::from_secs(10)), Ok(13));
+    *guard = 5;
+    drop(guard);
+    handle.join().unwrap();
+
+    assert_eq!(*id0.get(), 5);
+    assert_eq!(map.len(), 2)
+}
@@ -1,22 +1,39 @@
 use deadlock::{
-    SlotMap, SlotMapId, SlotMapIter, SlotMapIterMut, SlotMapRef, SlotMapRefMut, SlotMapShardRef,
+    LeastLoaded, MappedSlotMapRef, MappedSlotMapRefMut, RawKey, RoundRobin, ShardLoads, SlotMap,
+    SlotMapBuilder, SlotMapBuilderError, SlotMapId, SlotMapIter, SlotMapIterMut, SlotMapManyRefMut,
+    SlotMapRef, SlotMapRefMut, SlotMapRefUpgradable, SlotMapShardRef, SlotMapShardRefMut,
+    SlotMapSharedId, SlotMapSnapshot, SlotMapTryGetError, SlotMapWeakId, ThreadAffinity,
+    ThreadHash,
 };
 use std::{
-    iter,
+    cmp,
+    collections::HashSet,
+    env, iter, mem,
+    panic::{self, AssertUnwindSafe},
+    process::Command,
     sync::{Arc, Mutex},
     thread,
+    time::{Duration, Instant},
 };
 
-fn _slotmap_send_sync_checks<'a>() {
+fn _slotmap_send_sync_checks() {
     fn assert_send_sync<T: Send + Sync>() {}
 
     assert_send_sync::<SlotMap<i32>>();
     assert_send_sync::<SlotMapId<i32>>();
-    assert_send_sync::<SlotMapRef<'a, i32>>();
-    assert_send_sync::<SlotMapRefMut<'a, i32>>();
-    assert_send_sync::<SlotMapShardRef<'a, i32>>();
-    assert_send_sync::<SlotMapIter<'a, i32>>();
-    assert_send_sync::<SlotMapIterMut<'a, i32>>()
+    assert_send_sync::<SlotMapSharedId<i32>>();
+    assert_send_sync::<SlotMapWeakId<i32>>();
+    assert_send_sync::<SlotMapRef<'_, i32>>();
+    assert_send_sync::<SlotMapRefUpgradable<'_, i32>>();
+    assert_send_sync::<SlotMapRefMut<'_, i32>>();
+    assert_send_sync::<MappedSlotMapRef<'_, (i32, i32), i32>>();
+    assert_send_sync::<MappedSlotMapRefMut<'_, (i32, i32), i32>>();
+    assert_send_sync::<SlotMapShardRef<'_, i32>>();
+    assert_send_sync::<SlotMapShardRefMut<'_, i32>>();
+    assert_send_sync::<SlotMapSnapshot<'_, i32>>();
+    assert_send_sync::<SlotMapManyRefMut<'_, i32, 2>>();
+    assert_send_sync::<SlotMapIter<'_, i32>>();
+    assert_send_sync::<SlotMapIterMut<'_, i32>>()
 }
 
 #[test]
@@ -27,7 +44,7 @@ fn shards_iter_yields_all_entries() {
 
     let mut collected = map
         .shards()
-        .flat_map(|shard| shard.iter().copied().collect::<Vec<_>>())
+        .flat_map(|shard| shard.iter().map(|(_, value)| *value).collect::<Vec<_>>())
         .collect::<Vec<_>>();
     collected.sort_unstable();
 
@@ -142,7 +159,7 @@ fn iter_yields_all_entries() {
     let n = 32;
     let _ids = (0..n).map(|i| map.insert(i * 10)).collect::<Vec<_>>();
 
-    let collected = map.iter().map(|r| *r).collect::<Vec<_>>();
+    let collected = map.iter().map(|(_, r)| *r).collect::<Vec<_>>();
     assert_eq!(collected.len(), n);
 
     let mut sorted = collected;
@@ -155,16 +172,733 @@ fn iter_mut_modifies_values() {
     let map = SlotMap::new();
     let _ids = (0..8).map(|i| map.insert(i)).collect::<Vec<_>>();
 
-    for mut r in map.iter_mut() {
+    for (_, mut r) in map.iter_mut() {
         *r += 100;
     }
 
-    let collected = map.iter().map(|r| *r).collect::<Vec<_>>();
+    let collected = map.iter().map(|(_, r)| *r).collect::<Vec<_>>();
     let mut sorted = collected;
     sorted.sort_unstable();
     assert_eq!(sorted, (100..108).collect::<Vec<_>>())
 }
 
+#[test]
+fn iter_keys_resolve_to_their_handles() {
+    let map = SlotMap::new();
+    let ids = map.insert_many(0..16);
+
+    for (key, mut r) in map.iter_mut() {
+        assert_eq!(key, ids[*r].key());
+        *r += 1
+    }
+
+    for (key, r) in map.iter() {
+        assert_eq!(key, ids[*r - 1].key());
+        drop(r);
+        assert_eq!(
+            *map.get(key).unwrap(),
+            *ids[*map.get(key).unwrap() - 1].get()
+        )
+    }
+
+    for shard in map.shards() {
+        for (key, value) in shard.iter() {
+            assert_eq!(key, ids[value - 1].key())
+        }
+    }
+}
+
+#[test]
+fn read_all_freezes_every_shard() {
+    let map = Arc::new(SlotMap::new());
+    let mut ids = map.insert_many(0..64);
+    let removed = ids[0].key();
+    ids.drain(..2);
+
+    let snapshot = map.read_all();
+    assert_eq!(snapshot.len(), 62);
+    assert!(!snapshot.is_empty());
+    assert!(snapshot.get(removed).is_none());
+
+    let mut values = snapshot
+        .iter()
+        .map(|(key, value)| {
+            assert_eq!(snapshot.get(key), Some(value));
+            *value
+        })
+        .collect::<Vec<_>>();
+    values.sort_unstable();
+    assert_eq!(values, (2..64).collect::<Vec<_>>());
+
+    let writer = map.clone();
+    let handle = thread::spawn(move || drop(writer.insert(64)));
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(snapshot.len(), 62);
+    drop(snapshot);
+    handle.join().unwrap();
+    assert_eq!(map.read_all().len(), 62);
+}
+
+#[test]
+fn shards_mut_shares_lock_order_with_read_all() {
+    let map = Arc::new(SlotMap::new());
+    let _ids = map.insert_many(0..256);
+
+    let writer = {
+        let map = map.clone();
+        thread::spawn(move || {
+            for _ in 0..200 {
+                let shards = map.shards_mut().collect::<Vec<_>>();
+                assert_eq!(shards.iter().map(|shard| shard.len()).sum::<usize>(), 256)
+            }
+        })
+    };
+
+    for _ in 0..200 {
+        assert_eq!(map.read_all().len(), 256)
+    }
+
+    writer.join().unwrap()
+}
+
+#[test]
+fn shards_mut_updates_whole_shard_under_one_lock() {
+    let map = SlotMap::new();
+    let ids = map.insert_many(0..64);
+
+    for mut shard in map.shards_mut() {
+        assert_eq!(shard.len(), shard.iter().count());
+        assert!(shard.capacity() >= shard.len());
+
+        for (key, value) in shard.iter_mut() {
+            assert_eq!(key, ids[*value].key());
+            *value += 100
+        }
+
+        shard.for_each_mut(|_, value| *value *= 2);
+
+        for (_, value) in shard.iter() {
+            assert_eq!(
+                ids[*value / 2 - 100].try_get().err(),
+                Some(SlotMapTryGetError::Locked)
+            )
+        }
+    }
+
+    assert_eq!(map.shards().map(|shard| shard.len()).sum::<usize>(), 64);
+
+    for (i, id) in ids.iter().enumerate() {
+        assert_eq!(*id.get(), (i + 100) * 2)
+    }
+}
+
+#[test]
+fn iter_visits_survivors_exactly_once_under_removal() {
+    let map = SlotMapBuilder::new().num_shards(2).build().unwrap();
+    let mut ids = map
+        .insert_many(0..128)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+
+    let mut iter = map.iter();
+    let (lower, upper) = iter.size_hint();
+    assert_eq!(lower, 0);
+    assert!(upper.unwrap() >= 128);
+
+    let mut seen = Vec::new();
+
+    for (_, r) in iter.by_ref() {
+        let value = *r;
+        drop(r);
+        seen.push(value);
+
+        if value % 2 == 1 {
+            ids[value] = None;
+            drop(map.insert(1000 + value))
+        }
+    }
+
+    let mut survivors = seen.into_iter().filter(|v| v % 2 == 0).collect::<Vec<_>>();
+    survivors.sort_unstable();
+    assert_eq!(survivors, (0..64).map(|i| i * 2).collect::<Vec<_>>());
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+    assert_eq!(map.len(), 64)
+}
+
+#[test]
+fn debug_shows_values_shards_and_generations() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let id = map.insert("first");
+    drop(map.insert("gone"));
+    let other = map.insert("second");
+
+    assert_eq!(format!("{:?}", id.get()), "\"first\"");
+    assert_eq!(
+        format!("{:?}", other),
+        "SlotMapId { shard: 0, id: 1, generation: 1 }"
+    );
+
+    let debug = format!("{:?}", map);
+    assert!(debug.starts_with("SlotMap { shards: [Shard { index: 0, len: 2, values: {"));
+    assert!(debug.contains("\"first\"") && debug.contains("\"second\""));
+    assert!(!debug.contains("gone"));
+
+    let guard = id.get_mut();
+    assert!(format!("{:?}", map).contains("values: <locked>"));
+    drop(guard);
+    assert_eq!(
+        format!("{:?}", map.shards().next().unwrap()),
+        "{SlotMapKey { shard: 0, id: 0, generation: 0 }: \"first\", \
+         SlotMapKey { shard: 0, id: 1, generation: 1 }: \"second\"}"
+    )
+}
+
+#[test]
+fn builder_non_power_of_two_shards_spreads_round_robin() {
+    let map = SlotMapBuilder::new()
+        .num_shards(3)
+        .capacity(4)
+        .selector(RoundRobin::strict())
+        .build()
+        .unwrap();
+    let _ids = (0..9).map(|i| map.insert(i)).collect::<Vec<_>>();
+
+    assert_eq!(map.num_shards(), 3);
+    assert_eq!(map.len(), 9);
+
+    for shard in map.shards() {
+        assert_eq!(shard.iter().count(), 3)
+    }
+}
+
+#[test]
+fn round_robin_new_matches_default() {
+    assert_eq!(
+        format!("{:?}", RoundRobin::new()),
+        format!("{:?}", RoundRobin::default())
+    );
+    assert_ne!(
+        format!("{:?}", RoundRobin::strict()),
+        format!("{:?}", RoundRobin::default())
+    )
+}
+
+#[test]
+fn builder_single_shard_with_sampling() {
+    let map = SlotMapBuilder::new()
+        .num_shards(1)
+        .selector(RoundRobin::sampled(8))
+        .build()
+        .unwrap();
+    let ids = (0..16).map(|i| map.insert(i)).collect::<Vec<_>>();
+
+    assert_eq!(map.num_shards(), 1);
+
+    for (i, id) in ids.iter().enumerate() {
+        assert_eq!(*id.get(), i)
+    }
+}
+
+#[test]
+fn builder_rejects_invalid_configuration() {
+    let zero_shards = SlotMapBuilder::new().num_shards(0).build::<i32>();
+    assert_eq!(zero_shards.err(), Some(SlotMapBuilderError::ZeroShards));
+
+    let overflow = SlotMapBuilder::new()
+        .num_shards(1)
+        .capacity(usize::MAX)
+        .build::<i32>();
+    assert_eq!(overflow.err(), Some(SlotMapBuilderError::CapacityOverflow));
+
+    let unallocatable = SlotMapBuilder::new()
+        .num_shards(1)
+        .capacity(1 << 50)
+        .build::<u8>();
+    assert_eq!(
+        unallocatable.err(),
+        Some(SlotMapBuilderError::CapacityOverflow)
+    )
+}
+
+#[test]
+fn custom_selector_is_deterministic() {
+    let map = SlotMapBuilder::new()
+        .num_shards(4)
+        .selector(|loads: &ShardLoads<'_>| loads.len() - 1)
+        .build()
+        .unwrap();
+    let _ids = (0..8).map(|i| map.insert(i)).collect::<Vec<_>>();
+
+    let counts = map
+        .shards()
+        .map(|shard| shard.iter().count())
+        .collect::<Vec<_>>();
+    assert_eq!(counts, [0, 0, 0, 8])
+}
+
+#[test]
+#[should_panic(expected = "out-of-range")]
+fn custom_selector_out_of_range_panics() {
+    let map = SlotMapBuilder::new()
+        .num_shards(2)
+        .selector(|_: &ShardLoads<'_>| 2)
+        .build()
+        .unwrap();
+    let _id = map.insert(0);
+}
+
+#[test]
+fn least_loaded_selector_balances_after_removal() {
+    let map = SlotMapBuilder::new()
+        .num_shards(4)
+        .selector(LeastLoaded)
+        .build()
+        .unwrap();
+    let mut ids = (0..8).map(|i| map.insert(i)).collect::<Vec<_>>();
+    ids.truncate(6);
+    ids.extend((0..2).map(|i| map.insert(i)));
+
+    for shard in map.shards() {
+        assert_eq!(shard.iter().count(), 2)
+    }
+}
+
+#[test]
+fn insert_many_keeps_order_and_balances_shards() {
+    let map = SlotMapBuilder::new()
+        .num_shards(4)
+        .selector(LeastLoaded)
+        .build()
+        .unwrap();
+    let _id = map.insert(usize::MAX);
+    let mut ids = map.insert_many(0..15);
+    assert_eq!(map.len(), 16);
+
+    for shard in map.shards() {
+        assert_eq!(shard.iter().count(), 4)
+    }
+
+    map.insert_many_into(vec![15, 16], &mut ids);
+    assert_eq!(ids.len(), 17);
+
+    for (i, id) in ids.into_iter().enumerate() {
+        assert_eq!(id.into_inner(), i)
+    }
+
+    assert_eq!(map.len(), 1)
+}
+
+#[test]
+fn retain_evicts_and_invalidates_handles() {
+    let map = SlotMap::new();
+    let ids = map.insert_many(0..16);
+    let weak = ids[1].downgrade();
+
+    map.retain(|value| *value % 2 == 0);
+    assert_eq!(map.len(), 8);
+    assert!(!weak.is_alive());
+
+    let fresh = map.insert_many(16..24);
+    assert_eq!(map.len(), 16);
+
+    for (i, id) in ids.iter().enumerate() {
+        assert_eq!(id.is_evicted(), i % 2 == 1);
+        let expected = if i % 2 == 0 {
+            Ok(i)
+        } else {
+            Err(SlotMapTryGetError::Evicted)
+        };
+        assert_eq!(id.try_get().map(|r| *r), expected);
+        assert_eq!(id.try_get_mut().map(|r| *r), expected)
+    }
+
+    let mut ids = ids.into_iter();
+    assert_eq!(ids.next().unwrap().try_into_inner(), Some(0));
+    assert_eq!(ids.next().unwrap().try_into_inner(), None);
+    drop(ids);
+    assert_eq!(map.len(), 8);
+    assert!(fresh.iter().all(|id| !id.is_evicted()))
+}
+
+#[test]
+fn remove_where_and_clear_return_and_drop_values() {
+    let map = SlotMap::new();
+    let ids = map.insert_many(0..8);
+    let shared = map.insert_shared(8);
+    let clone = shared.clone();
+    let raw = map.insert(9).into_raw();
+
+    let mut removed = map.remove_where(|value| *value >= 6);
+    removed.sort_unstable();
+    assert_eq!(removed, vec![6, 7, 8, 9]);
+    assert!(shared.is_evicted());
+    assert_eq!(clone.strong_count(), 0);
+    assert!(map.try_id_from_raw(raw).is_none());
+    drop(shared);
+    drop(clone);
+    assert_eq!(map.len(), 6);
+
+    map.clear();
+    assert!(map.is_empty());
+    assert!(ids.iter().all(|id| id.is_evicted()));
+    drop(ids);
+    assert!(map.is_empty())
+}
+
+#[test]
+fn remove_where_keeps_len_when_predicate_panics() {
+    let map = SlotMap::new();
+    let ids = map.insert_many(0..16);
+    let mut calls = 0;
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        map.remove_where(|_| {
+            calls += 1;
+            assert!(calls <= 5, "predicate failed");
+            true
+        })
+    }));
+
+    assert!(result.is_err());
+    let alive = ids.iter().filter(|id| !id.is_evicted()).count();
+    assert_eq!(alive, 11);
+    assert_eq!(map.len(), alive);
+    drop(ids);
+    assert!(map.is_empty())
+}
+
+#[test]
+#[should_panic(expected = "evicted")]
+fn get_on_evicted_handle_panics() {
+    let map = SlotMap::new();
+    let id = map.insert(0);
+    map.clear();
+    let _r = id.get();
+}
+
+#[test]
+fn thread_selectors_stick_to_one_shard() {
+    for map in vec![
+        SlotMapBuilder::new().num_shards(8).selector(ThreadAffinity),
+        SlotMapBuilder::new().num_shards(8).selector(ThreadHash),
+    ]
+    .into_iter()
+    .map(|builder| builder.build().unwrap())
+    {
+        let _ids = (0..16).map(|i| map.insert(i)).collect::<Vec<_>>();
+
+        let used = map
+            .shards()
+            .filter(|shard| shard.iter().count() != 0)
+            .count();
+        assert_eq!(used, 1)
+    }
+}
+
+#[test]
+fn reserve_keeps_capacity_stable_while_inserting() {
+    let map = SlotMapBuilder::new()
+        .num_shards(4)
+        .selector(RoundRobin::strict())
+        .build()
+        .unwrap();
+    map.reserve(32);
+    let capacity = map.capacity();
+    assert_eq!(capacity, 32);
+
+    let _ids = (0..32).map(|i| map.insert(i)).collect::<Vec<_>>();
+    assert_eq!(map.capacity(), capacity);
+
+    for shard in map.shards() {
+        assert!(shard.capacity() >= 8)
+    }
+}
+
+#[test]
+fn reserve_unallocatable_aborts_cleanly() {
+    const CHILD: &str = "DEADLOCK_RESERVE_CHILD";
+
+    if env::var_os(CHILD).is_some() {
+        SlotMapBuilder::new()
+            .num_shards(1)
+            .build::<u8>()
+            .unwrap()
+            .reserve(1 << 50);
+        return;
+    }
+
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["reserve_unallocatable_aborts_cleanly", "--exact"])
+        .env(CHILD, "1")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("memory allocation of"), "{}", stderr)
+}
+
+#[test]
+fn with_capacity_preallocates_total() {
+    let map = SlotMap::<i32>::with_capacity(100);
+    assert!(map.capacity() >= 100);
+    assert!(map.is_empty())
+}
+
+#[test]
+fn weak_id_follows_owner() {
+    let map = SlotMap::new();
+    let id = map.insert(7);
+    let weak = id.downgrade();
+
+    assert!(weak.is_alive());
+    assert_eq!(*weak.get().unwrap(), 7);
+    *weak.get_mut().unwrap() = 8;
+    assert_eq!(*id.get(), 8);
+
+    drop(id);
+    assert!(!weak.is_alive());
+    assert!(weak.get().is_none());
+    assert!(weak.get_mut().is_none())
+}
+
+#[test]
+fn weak_id_does_not_see_reused_slot() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let id = map.insert(1);
+    let weak = id.downgrade();
+    drop(id);
+
+    let _reused = map.insert(2);
+    assert_eq!(map.len(), 1);
+    assert!(weak.get().is_none());
+    assert!(weak.clone().get().is_none())
+}
+
+#[test]
+fn shared_id_removes_on_last_drop() {
+    let map = SlotMap::new();
+    let id0 = map.insert_shared(String::from("a"));
+    let id1 = id0.clone();
+    assert_eq!(id0.strong_count(), 2);
+
+    id1.get_mut().push('b');
+    assert_eq!(*id0.get(), "ab");
+
+    drop(id0);
+    assert_eq!(map.len(), 1);
+    assert_eq!(id1.strong_count(), 1);
+
+    drop(id1);
+    assert!(map.is_empty())
+}
+
+#[test]
+fn shared_id_try_unwrap_requires_sole_owner() {
+    let map = SlotMap::new();
+    let id0 = map.insert(5).into_shared();
+    let id1 = id0.clone();
+
+    let id0 = id0.try_unwrap().unwrap_err();
+    drop(id1);
+    assert_eq!(id0.try_unwrap().unwrap(), 5);
+    assert!(map.is_empty())
+}
+
+#[test]
+fn weak_upgrade_only_for_shared_owners() {
+    let map = SlotMap::new();
+    let unique = map.insert(1);
+    assert!(unique.downgrade().upgrade().is_none());
+
+    let shared = map.insert_shared(2);
+    let weak = shared.downgrade();
+    let upgraded = weak.upgrade().unwrap();
+    assert_eq!(shared.strong_count(), 2);
+
+    drop(shared);
+    assert_eq!(*upgraded.get(), 2);
+    drop(upgraded);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(map.len(), 1)
+}
+
+#[test]
+fn raw_key_round_trips_through_u64() {
+    let map = SlotMapBuilder::new().num_shards(5).build().unwrap();
+    let ids = (0..20).map(|i| map.insert(i)).collect::<Vec<_>>();
+    let bits = ids
+        .into_iter()
+        .map(|id| u64::from(id.into_raw()))
+        .collect::<Vec<_>>();
+    assert_eq!(map.len(), 20);
+
+    for (i, bits) in bits.into_iter().enumerate() {
+        let id = unsafe { map.id_from_raw(RawKey::from(bits)) };
+        assert_eq!(id.into_inner(), i)
+    }
+
+    assert!(map.is_empty())
+}
+
+#[test]
+fn try_id_from_raw_rejects_restored_and_stale_keys() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let key = map.insert(1).into_raw();
+
+    let id = map.try_id_from_raw(key).unwrap();
+    assert!(map.try_id_from_raw(key).is_none());
+    assert!(id.downgrade().upgrade().is_none());
+
+    drop(id);
+    let _reused = map.insert(2);
+    assert!(map.try_id_from_raw(key).is_none());
+    assert!(map
+        .try_id_from_raw(RawKey::from_bits(key.to_bits() + 1))
+        .is_none());
+    assert!(map.try_id_from_raw(RawKey::from_bits(u64::MAX)).is_none())
+}
+
+#[test]
+fn try_id_from_raw_rejects_keys_of_other_maps() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let other = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let key = map.insert(1).into_raw();
+    let other_key = other.insert(2).into_raw();
+
+    assert!(other.try_id_from_raw(key).is_none());
+    assert!(map.try_id_from_raw(other_key).is_none());
+    assert_eq!(*map.try_id_from_raw(key).unwrap().get(), 1);
+    assert_eq!(*other.try_id_from_raw(other_key).unwrap().get(), 2)
+}
+
+#[test]
+fn builder_rejects_too_many_shards() {
+    let result = SlotMapBuilder::new().num_shards(1 << 13).build::<i32>();
+    assert_eq!(result.err(), Some(SlotMapBuilderError::TooManyShards))
+}
+
+#[test]
+fn key_resolves_until_owner_is_dropped() {
+    let map = SlotMapBuilder::new().num_shards(2).build().unwrap();
+    let ids = (0..4).map(|i| map.insert(i)).collect::<Vec<_>>();
+    let keys = ids.iter().map(SlotMapId::key).collect::<Vec<_>>();
+
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(*map.get(*key).unwrap(), i)
+    }
+
+    *map.get_mut(keys[1]).unwrap() = 10;
+    assert_eq!(*ids[1].get(), 10);
+
+    drop(ids);
+    let _reused = (0..4).map(|i| map.insert(i)).collect::<Vec<_>>();
+
+    for key in keys {
+        assert!(map.get(key).is_none());
+        assert!(map.get_mut(key).is_none())
+    }
+}
+
+#[test]
+fn key_matches_across_handle_kinds() {
+    let map = SlotMap::new();
+    let shared = map.insert_shared(1);
+    let weak = shared.downgrade();
+    assert_eq!(shared.key(), weak.key());
+    assert_eq!(*map.get(weak.key()).unwrap(), 1);
+}
+
+#[test]
+fn try_and_timed_access_give_up_on_contention() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let id0 = map.insert(0);
+    let id1 = map.insert(1);
+
+    let r = id0.get();
+    assert_eq!(*id1.try_get().unwrap(), 1);
+    assert_eq!(id1.try_get_mut().err(), Some(SlotMapTryGetError::Locked));
+    assert_eq!(
+        id1.get_mut_for(Duration::from_millis(10)).err(),
+        Some(SlotMapTryGetError::Locked)
+    );
+    assert_eq!(
+        id1.get_mut_until(Instant::now() + Duration::from_millis(10))
+            .err(),
+        Some(SlotMapTryGetError::Locked)
+    );
+    drop(r);
+
+    let w = id0.get_mut();
+    assert_eq!(id1.try_get().err(), Some(SlotMapTryGetError::Locked));
+    assert_eq!(
+        id1.get_for(Duration::from_millis(10)).err(),
+        Some(SlotMapTryGetError::Locked)
+    );
+    assert_eq!(
+        id1.get_until(Instant::now() + Duration::from_millis(10))
+            .err(),
+        Some(SlotMapTryGetError::Locked)
+    );
+    drop(w);
+
+    *id1.try_get_mut().unwrap() = 2;
+    assert_eq!(*id1.get_for(Duration::from_millis(10)).unwrap(), 2);
+}
+
+#[test]
+fn upgradable_coexists_with_readers_then_upgrades() {
+    let map = SlotMapBuilder::new().num_shards(1).build().unwrap();
+    let id0 = map.insert(1);
+    let id1 = map.insert(2);
+
+    let upgradable = id0.get_upgradable();
+    assert_eq!(*id1.try_get().unwrap(), 2);
+    assert_eq!(id1.try_get_mut().err(), Some(SlotMapTryGetError::Locked));
+
+    if *upgradable == 1 {
+        let mut r = upgradable.upgrade();
+        *r = 3;
+        let r = r.downgrade();
+        assert_eq!(*r, 3);
+        assert_eq!(*id1.try_get().unwrap(), 2);
+        assert_eq!(id1.try_get_mut().err(), Some(SlotMapTryGetError::Locked));
+    }
+
+    assert_eq!(*id0.get(), 3);
+}
+
+#[test]
+fn map_projects_guard_to_field() {
+    let map = SlotMap::new();
+    let id = map.insert((1, String::from("one")));
+
+    let name = SlotMapRef::map(id.get(), |(_, name)| name.as_str());
+    assert_eq!(&*name, "one");
+    assert_eq!(id.try_get_mut().err(), Some(SlotMapTryGetError::Locked));
+    drop(name);
+
+    let mut number = SlotMapRefMut::map(id.get_mut(), |(number, _)| number);
+    *number += 1;
+    let mut number = MappedSlotMapRefMut::map(number, |number| number);
+    *number += 1;
+    drop(number);
+    assert_eq!(id.get().0, 3);
+
+    let r = SlotMapRef::filter_map(id.get(), |(_, name)| name.strip_prefix('x'))
+        .err()
+        .unwrap();
+    let (r, error) = SlotMapRef::try_map(r, |(number, _)| Err::<&i32, _>(*number))
+        .err()
+        .unwrap();
+    assert_eq!(error, 3);
+    let first =
+        MappedSlotMapRef::filter_map(SlotMapRef::map(r, |(_, name)| name), |name| name.get(..1))
+            .ok()
+            .unwrap();
+    assert_eq!(&*first, "o");
+}
+
 #[test]
 fn send_sync_multi_threaded_insert() {
     let map = Arc::new(SlotMap::new());
@@ -211,3 +945,113 @@ fn send_sync_multi_threaded_iter() {
         handle.join().unwrap()
     }
 }
+
+#[test]
+fn handles_compare_by_map_shard_and_slot() {
+    let map = SlotMap::new();
+    let other = SlotMap::new();
+    let mut ids = map.insert_many(0..16);
+    let foreign = other.insert(0);
+
+    assert!(ids.iter().all(|id| map.contains(id)));
+    assert!(!map.contains(&foreign));
+    assert!(other.contains(&foreign));
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 16);
+    assert!(ids.iter().all(|id| *id < foreign));
+
+    ids.sort();
+    assert!(ids.windows(2).all(|pair| pair[0].key() < pair[1].key()));
+
+    let shared = map.insert_shared(16);
+    assert!(shared == shared.clone());
+    assert_eq!(shared.clone().cmp(&shared), cmp::Ordering::Equal);
+
+    let evicted = ids.pop().unwrap();
+    let value = *evicted.get();
+    map.retain(|other| *other != value);
+    assert!(!map.contains(&evicted));
+    assert!(ids.iter().all(|id| *id != evicted))
+}
+
+#[test]
+fn move_to_transfers_value_between_maps() {
+    let active = Arc::new(SlotMap::new());
+    let draining = Arc::new(SlotMap::new());
+    let id = active.insert(7);
+
+    let id = id.move_to(&draining);
+    assert!(draining.contains(&id));
+    assert_eq!(*id.get(), 7);
+    assert_eq!((active.len(), draining.len()), (0, 1));
+
+    let id = id.move_to(&draining);
+    assert_eq!(draining.len(), 1);
+    drop(id.move_to(&active));
+    assert!(active.is_empty() && draining.is_empty());
+
+    let handles = vec![(active.clone(), draining.clone()), (draining, active)]
+        .into_iter()
+        .map(|(from, to)| {
+            thread::spawn(move || {
+                let mut id = from.insert(0);
+
+                for _ in 0..1000 {
+                    id = id.move_to(&to).move_to(&from);
+                    *id.get_mut() += 1
+                }
+
+                id.into_inner()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 1000)
+    }
+}
+
+#[test]
+fn get_many_mut_locks_shared_and_distinct_shards_once() {
+    let map = Arc::new(SlotMapBuilder::new().num_shards(2).build().unwrap());
+    let ids = Arc::new(map.insert_many(vec![100, 0, 50, 0]));
+
+    {
+        let mut refs = map.get_many_mut([&ids[0], &ids[1], &ids[2], &ids[3]]);
+        let [a, b, c, d] = refs.each_mut();
+        mem::swap(a, b);
+        *c -= 25;
+        *d += 25;
+        assert_eq!(format!("{:?}", refs), "[0, 100, 25, 25]");
+    }
+
+    let handles = vec![(0, 1), (1, 0), (2, 3), (3, 0)]
+        .into_iter()
+        .map(|(from, to)| {
+            let map = map.clone();
+            let ids = ids.clone();
+
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    let mut refs = map.get_many_mut([&ids[from], &ids[to]]);
+                    refs[0] -= 1;
+                    refs[1] += 1
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.join().unwrap()
+    }
+
+    let total = ids.iter().map(|id| *id.get()).sum::<i32>();
+    assert_eq!(total, 150)
+}
+
+#[test]
+#[should_panic(expected = "handle was passed more than once")]
+fn get_many_mut_rejects_duplicate_handles() {
+    let map = SlotMap::new();
+    let id = map.insert(0);
+    map.get_many_mut([&id, &id]);
+}